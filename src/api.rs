@@ -0,0 +1,272 @@
+//! Request and response types for `snapd`'s endpoints.
+//!
+//! Each endpoint (or, where one endpoint serves several distinct purposes, each use of it) has its
+//! own request type, implementing [`Get`] with the type of its response as the [`Get::Payload`],
+//! or [`Put`] and [`Post`] for endpoints that change something, with the [`Envelope`] `snapd`
+//! responds in. The few endpoints taking forms rather than JSON implement [`PostForm`].
+
+use std::borrow::Cow;
+
+use hyper::HeaderMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use url::Url;
+
+use changes::ChangeId;
+
+/// Implements the usual constructors and conversions for a newtype over `Cow<'a, str>`.
+macro_rules! cow_str_newtype {
+    ($name:ident) => {
+        impl<'a> $name<'a> {
+            pub fn new(value: impl Into<::std::borrow::Cow<'a, str>>) -> Self {
+                Self(value.into())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Detaches this value from whatever it was borrowed from.
+            pub fn into_owned(self) -> $name<'static> {
+                $name(::std::borrow::Cow::Owned(self.0.into_owned()))
+            }
+        }
+
+        impl<'a> From<&'a str> for $name<'a> {
+            fn from(value: &'a str) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl From<String> for $name<'_> {
+            fn from(value: String) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl AsRef<str> for $name<'_> {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl ::std::fmt::Display for $name<'_> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+/// A response captured from `snapd`, read from `tests/fixtures`.
+#[cfg(test)]
+macro_rules! fixture {
+    ($name:literal) => {
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/",
+            $name
+        ))
+    };
+}
+
+pub mod aliases;
+pub mod apps;
+pub mod assertions;
+pub mod changes;
+pub mod conf;
+pub mod debug;
+pub mod find;
+pub mod interfaces;
+pub mod model;
+pub mod notices;
+pub mod recovery_keys;
+pub mod snap;
+pub mod snapctl;
+pub mod snaps;
+pub mod snapshots;
+pub mod system_info;
+pub mod systems;
+pub mod themes;
+pub mod time;
+pub mod warnings;
+
+/// An endpoint that can be queried with a GET request.
+pub trait Get {
+    /// The type of the `result` field in `snapd`'s response.
+    type Payload: DeserializeOwned;
+
+    /// Points `base_url` at this endpoint, setting its path and query.
+    fn url(&self, base_url: Url) -> Url;
+
+    /// Headers to send along with the request, such as `Accept-Language`. There are none by
+    /// default.
+    fn headers(&self) -> HeaderMap {
+        HeaderMap::new()
+    }
+}
+
+/// An endpoint whose results can be fetched a page at a time, each request picking up after the
+/// last, such as [`GetNotices`](notices::GetNotices).
+///
+/// [`SnapdClient::pages`](crate::SnapdClient::pages) fetches the pages one after another.
+pub trait Paginated: Get {
+    /// Moves the request on past `page`, the page just fetched, returning `false` if it was empty,
+    /// which ends the pages.
+    fn advance(&mut self, page: &Self::Payload) -> bool;
+}
+
+/// An endpoint that accepts a PUT request, starting an asynchronous change.
+pub trait Put {
+    /// The type of the JSON body sent with the request.
+    type Body<'a>: Serialize
+    where
+        Self: 'a;
+
+    /// Points `base_url` at this endpoint, setting its path and query.
+    fn url(&self, base_url: Url) -> Url;
+
+    fn body(&self) -> Self::Body<'_>;
+}
+
+/// An endpoint that accepts a POST request.
+pub trait Post {
+    /// The type of the JSON body sent with the request.
+    type Body<'a>: Serialize
+    where
+        Self: 'a;
+
+    /// How `snapd` responds: with an [`AsyncResponse`] if the request starts a change, or with a
+    /// [`SyncResponse`] if it's carried out before `snapd` responds.
+    type Response: Envelope;
+
+    /// Points `base_url` at this endpoint, setting its path and query.
+    fn url(&self, base_url: Url) -> Url;
+
+    fn body(&self) -> Self::Body<'_>;
+}
+
+/// An endpoint that accepts a POST request with a `multipart/form-data` body, starting an
+/// asynchronous change.
+///
+/// `snapd` takes forms where it may be sent files, such as when installing a snap from a file.
+pub trait PostForm {
+    /// Points `base_url` at this endpoint, setting its path and query.
+    fn url(&self, base_url: Url) -> Url;
+
+    /// The form's fields, as their names and values, in the order they're sent.
+    fn fields(&self) -> Vec<(&'static str, Cow<'_, [u8]>)>;
+}
+
+/// An endpoint that responds to a GET request with raw bytes, such as an archive, which are read
+/// as they arrive rather than decoded.
+pub trait GetStream {
+    /// Points `base_url` at this endpoint, setting its path and query.
+    fn url(&self, base_url: Url) -> Url;
+}
+
+/// An endpoint that accepts a POST request with a raw body, such as an archive.
+pub trait PostBytes {
+    /// The `Content-Type` the body is sent with.
+    const CONTENT_TYPE: &'static str;
+
+    /// How `snapd` responds: with an [`AsyncResponse`] if the request starts a change, or with a
+    /// [`SyncResponse`] if it's carried out before `snapd` responds.
+    type Response: Envelope;
+
+    /// Points `base_url` at this endpoint, setting its path and query.
+    fn url(&self, base_url: Url) -> Url;
+
+    fn body(&self) -> Cow<'_, [u8]>;
+}
+
+/// An envelope `snapd` wraps its responses in, holding what the request produced.
+pub trait Envelope: DeserializeOwned {
+    /// What the request produced.
+    type Output;
+
+    fn into_output(self) -> Self::Output;
+}
+
+/// The envelope of a response to a request that was carried out before `snapd` responded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyncResponse<T> {
+    pub result: T,
+}
+
+impl<T: DeserializeOwned> Envelope for SyncResponse<T> {
+    type Output = T;
+
+    fn into_output(self) -> T {
+        self.result
+    }
+}
+
+/// The envelope of a response to a request that started a change.
+///
+/// The change's ID is sent in `change` rather than `result`, which is `null` for these responses.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AsyncResponse {
+    pub change: ChangeId,
+}
+
+impl Envelope for AsyncResponse {
+    type Output = ChangeId;
+
+    fn into_output(self) -> ChangeId {
+        self.change
+    }
+}
+
+/// The `result` of an error response from `snapd`.
+#[derive(Clone, Debug, Error, Serialize, Deserialize)]
+#[error("{message}")]
+pub struct SnapdError {
+    pub message: String,
+    /// A machine-readable identifier for the error, such as `snap-not-found`.
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Extra information about the error, whose shape depends on [`kind`](Self::kind).
+    ///
+    /// It's boxed to keep errors small, as a [`Value`] is large with the `preserve-order` feature.
+    #[serde(default)]
+    pub value: Option<Box<Value>>,
+}
+
+/// Decodes the `result` of the sync response `json`, as the client does.
+#[cfg(test)]
+pub(crate) fn decode_result<T: DeserializeOwned>(json: &str) -> T {
+    serde_json::from_str::<SyncResponse<T>>(json)
+        .expect("fixture decodes")
+        .result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn async_envelope_holds_the_change() {
+        let response: AsyncResponse =
+            serde_json::from_str(fixture!("async_response.json")).unwrap();
+        assert_eq!(response.into_output().as_str(), "42");
+    }
+
+    /// Decodes `json` as the response to the POST `P`.
+    fn post_output<P: Post>(json: &str) -> <P::Response as Envelope>::Output {
+        serde_json::from_str::<P::Response>(json)
+            .unwrap()
+            .into_output()
+    }
+
+    #[test]
+    fn posts_expect_their_own_envelope() {
+        let change = post_output::<changes::AbortChange>(fixture!("abort_change.json"));
+        assert_eq!(change.id.as_str(), "42");
+        assert_eq!(change.status, changes::ChangeStatus::Abort);
+
+        let id = post_output::<snaps::InstallSnap>(fixture!("async_response.json"));
+        assert_eq!(id.as_str(), "42");
+    }
+}
@@ -0,0 +1,510 @@
+//! Aliases for snap commands, via `/v2/aliases`.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use url::Url;
+
+use super::{
+    changes::{Change, ChangeId, ChangeStatus},
+    snap::{App, SnapCommand, SnapName},
+    AsyncResponse, Get, Post,
+};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// The name of an alias, which runs a snap's command without its `snap.` prefix.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SnapAlias<'a>(Cow<'a, str>);
+
+cow_str_newtype!(SnapAlias);
+
+impl<'a> SnapAlias<'a> {
+    /// Checks `value` is an alias `snapd` accepts: a letter or digit, followed by letters, digits,
+    /// `-`, `_` and `.`.
+    pub fn try_new(value: impl Into<Cow<'a, str>>) -> Result<Self, InvalidAliasError> {
+        let value = value.into();
+        let mut chars = value.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+        if valid {
+            Ok(Self(value))
+        } else {
+            Err(InvalidAliasError(value.into_owned()))
+        }
+    }
+}
+
+/// The error returned when a string isn't a valid [`SnapAlias`].
+#[derive(Clone, Debug, Error)]
+#[error("invalid alias {0:?}: aliases are letters, digits, '-', '_' and '.', and start with a letter or digit")]
+pub struct InvalidAliasError(String);
+
+/// The ways changing an alias by name can fail.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum AliasError {
+    #[error(transparent)]
+    Request(#[from] SnapdRequestError),
+    #[error(transparent)]
+    InvalidAlias(#[from] InvalidAliasError),
+    #[error("changing aliases failed: {}", .0.err.as_deref().unwrap_or("unknown error"))]
+    ChangeFailed(Box<Change<'static>>),
+}
+
+/// Every alias on the system, keyed by the snap that provides it and then by its name.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Aliases<'a> {
+    pub snaps: HashMap<SnapName<'a>, HashMap<SnapAlias<'a>, AliasInfo<'a>>>,
+}
+
+impl<'a> Aliases<'a> {
+    /// Adds an alias provided by `snap`, returning any alias of the same name it replaces.
+    pub fn insert(
+        &mut self,
+        snap: SnapName<'a>,
+        alias: SnapAlias<'a>,
+        info: AliasInfo<'a>,
+    ) -> Option<AliasInfo<'a>> {
+        self.snaps.entry(snap).or_default().insert(alias, info)
+    }
+}
+
+impl<'a> FromIterator<(SnapName<'a>, SnapAlias<'a>, AliasInfo<'a>)> for Aliases<'a> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (SnapName<'a>, SnapAlias<'a>, AliasInfo<'a>)>,
+    {
+        let mut aliases = Self::default();
+        for (snap, alias, info) in iter {
+            aliases.insert(snap, alias, info);
+        }
+        aliases
+    }
+}
+
+/// What an alias runs, and why it exists.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawAliasInfo<'a>", into = "RawAliasInfo<'a>")]
+pub struct AliasInfo<'a> {
+    /// The command the alias runs. `snapd` sends a command running the app that shares its snap's
+    /// name as just the snap's name, such as `foo`, which has no [`app`](SnapCommand::app).
+    pub command: SnapCommand<'a, 'a>,
+    pub status: AliasStatus<'a>,
+}
+
+/// Whether an alias was set up automatically or by the user.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AliasStatus<'a> {
+    /// Declared by the snap, and granted by its snap-declaration.
+    Auto { auto: App<'a> },
+    /// Created by the user with `snap alias`, possibly overriding an automatic alias.
+    Manual {
+        manual: App<'a>,
+        auto: Option<App<'a>>,
+    },
+    /// An automatic alias the user disabled, along with the app it would run if enabled.
+    Disabled { auto: Option<App<'a>> },
+}
+
+/// [`AliasInfo`] as `snapd` sends it.
+///
+/// The `auto` and `manual` keys are both optional on the wire, whatever the status, so they're
+/// checked against the status by hand rather than with an internally tagged enum. A missing app
+/// falls back to the one in `command`, which is always the app the alias currently runs.
+#[derive(Serialize, Deserialize)]
+struct RawAliasInfo<'a> {
+    #[serde(deserialize_with = "owned_command")]
+    command: SnapCommand<'a, 'a>,
+    status: Cow<'a, str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    manual: Option<App<'a>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auto: Option<App<'a>>,
+}
+
+/// Decodes a command without borrowing from the input, so [`Aliases`] can be decoded from a
+/// response that doesn't outlive it.
+fn owned_command<'de, 'a, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SnapCommand<'a, 'a>, D::Error> {
+    String::deserialize(deserializer).map(SnapCommand::parse_owned)
+}
+
+impl<'a> TryFrom<RawAliasInfo<'a>> for AliasInfo<'a> {
+    type Error = String;
+
+    fn try_from(raw: RawAliasInfo<'a>) -> Result<Self, String> {
+        let command_app = || {
+            raw.command
+                .app
+                .clone()
+                .unwrap_or_else(|| App::new(raw.command.snap.as_str().to_owned()))
+        };
+
+        let status = match &*raw.status {
+            "auto" => AliasStatus::Auto {
+                auto: raw.auto.unwrap_or_else(command_app),
+            },
+            "manual" => AliasStatus::Manual {
+                manual: raw.manual.unwrap_or_else(command_app),
+                auto: raw.auto,
+            },
+            "disabled" => AliasStatus::Disabled { auto: raw.auto },
+            other => return Err(format!("unknown alias status {other:?}")),
+        };
+
+        Ok(Self {
+            command: raw.command,
+            status,
+        })
+    }
+}
+
+impl<'a> From<AliasInfo<'a>> for RawAliasInfo<'a> {
+    fn from(info: AliasInfo<'a>) -> Self {
+        let (status, auto, manual) = match info.status {
+            AliasStatus::Auto { auto } => ("auto", Some(auto), None),
+            AliasStatus::Manual { manual, auto } => ("manual", auto, Some(manual)),
+            AliasStatus::Disabled { auto } => ("disabled", auto, None),
+        };
+
+        Self {
+            command: info.command,
+            status: status.into(),
+            manual,
+            auto,
+        }
+    }
+}
+
+/// Lists every alias on the system, via `/v2/aliases`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GetAliases;
+
+impl Get for GetAliases {
+    type Payload = Aliases<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/aliases");
+        base_url
+    }
+}
+
+/// Changes aliases, via a POST to `/v2/aliases`.
+///
+/// `snapd` has no separate action for resetting a snap's aliases: [`Prefer`](Self::Prefer)
+/// re-enables its automatic aliases, which is what resetting them amounts to.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum AliasCommand<'a> {
+    /// Creates a manual alias running `snap.app`, as `snap alias` does.
+    Alias {
+        snap: SnapName<'a>,
+        app: App<'a>,
+        alias: SnapAlias<'a>,
+    },
+    /// Removes a manual alias, or disables an automatic one, as `snap unalias` does.
+    Unalias { alias: SnapAlias<'a> },
+    /// Disables every alias of `snap`, as `snap unalias` does when given a snap's name.
+    #[serde(rename = "unalias")]
+    UnaliasAll { snap: SnapName<'a> },
+    /// Enables every automatic alias of `snap`, disabling any conflicting aliases of other snaps,
+    /// as `snap prefer` does.
+    Prefer { snap: SnapName<'a> },
+}
+
+impl Post for AliasCommand<'_> {
+    type Body<'b>
+        = &'b Self
+    where
+        Self: 'b;
+    type Response = AsyncResponse;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/aliases");
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self
+    }
+}
+
+/// An alias a change created or removed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AliasChange<'a> {
+    pub snap: SnapName<'a>,
+    pub app: App<'a>,
+    pub alias: SnapAlias<'a>,
+}
+
+/// The aliases an [`AliasCommand`] changed, as reported in its change's
+/// [`data`](Change::data).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AliasChangeResult<'a> {
+    #[serde(default, rename = "aliases-added")]
+    pub added: Vec<AliasChange<'a>>,
+    #[serde(default, rename = "aliases-removed")]
+    pub removed: Vec<AliasChange<'a>>,
+    /// Automatic aliases the change disabled rather than removed, which
+    /// [`prefer_aliases`](SnapdClient::prefer_aliases) can enable again.
+    ///
+    /// `snapd` reports these among the removed aliases, so only the `_and_wait` methods, which
+    /// check the aliases once the change is done, tell them apart.
+    /// [`from_change`](Self::from_change) leaves this empty.
+    #[serde(default)]
+    pub disabled: Vec<AliasChange<'a>>,
+}
+
+impl AliasChangeResult<'_> {
+    /// Reads the aliases changed by `change`, which should come from an [`AliasCommand`].
+    pub fn from_change<'a>(
+        change: &'a Change<'_>,
+    ) -> Result<AliasChangeResult<'a>, serde_json::Error> {
+        match &change.data {
+            Value::Null => Ok(AliasChangeResult::default()),
+            data => AliasChangeResult::deserialize(data),
+        }
+    }
+}
+
+impl SnapdClient {
+    /// Creates the alias `alias` for `command`, as `snap alias` does, checking the alias is valid
+    /// before asking `snapd`.
+    ///
+    /// A command without an [`app`](SnapCommand::app) runs the app sharing its snap's name.
+    pub async fn alias(
+        &self,
+        command: SnapCommand<'_, '_>,
+        alias: &str,
+    ) -> Result<ChangeId, AliasError> {
+        let alias = SnapAlias::try_new(alias)?;
+        let app = command
+            .app
+            .unwrap_or_else(|| App::new(command.snap.as_str().to_owned()));
+        Ok(self
+            .post(&AliasCommand::Alias {
+                snap: command.snap,
+                app,
+                alias,
+            })
+            .await?)
+    }
+
+    /// Removes the manual alias `alias`, or disables it if it's automatic, as `snap unalias` does,
+    /// checking the alias is valid before asking `snapd`.
+    pub async fn unalias(&self, alias: &str) -> Result<ChangeId, AliasError> {
+        let alias = SnapAlias::try_new(alias)?;
+        Ok(self.post(&AliasCommand::Unalias { alias }).await?)
+    }
+
+    /// Enables every automatic alias of `snap`, taking over any aliases of the same names other
+    /// snaps have, as `snap prefer` does.
+    pub async fn prefer_aliases(&self, snap: SnapName<'_>) -> Result<ChangeId, SnapdRequestError> {
+        self.post(&AliasCommand::Prefer { snap }).await
+    }
+
+    /// Creates the alias `alias` for `command`, like [`alias`](Self::alias), and waits for the
+    /// change creating it, returning the aliases it changed.
+    pub async fn alias_and_wait(
+        &self,
+        command: SnapCommand<'_, '_>,
+        alias: &str,
+    ) -> Result<AliasChangeResult<'static>, AliasError> {
+        let id = self.alias(command, alias).await?;
+        self.wait_for_alias_change(&id).await
+    }
+
+    /// Removes or disables the alias `alias`, like [`unalias`](Self::unalias), and waits for the
+    /// change doing so, returning the aliases it removed or disabled.
+    pub async fn unalias_and_wait(
+        &self,
+        alias: &str,
+    ) -> Result<AliasChangeResult<'static>, AliasError> {
+        let id = self.unalias(alias).await?;
+        self.wait_for_alias_change(&id).await
+    }
+
+    /// Enables every automatic alias of `snap`, like [`prefer_aliases`](Self::prefer_aliases),
+    /// and waits for the change doing so, returning the aliases it enabled and the conflicting
+    /// ones it removed or disabled.
+    pub async fn prefer_aliases_and_wait(
+        &self,
+        snap: SnapName<'_>,
+    ) -> Result<AliasChangeResult<'static>, AliasError> {
+        let id = self.prefer_aliases(snap).await?;
+        self.wait_for_alias_change(&id).await
+    }
+
+    async fn wait_for_alias_change(
+        &self,
+        id: &ChangeId,
+    ) -> Result<AliasChangeResult<'static>, AliasError> {
+        let change = self.wait_for_change(id).await?;
+        if change.status != ChangeStatus::Done {
+            return Err(AliasError::ChangeFailed(Box::new(change)));
+        }
+
+        let mut result = match change.data {
+            Value::Null => AliasChangeResult::default(),
+            data => AliasChangeResult::deserialize(data).map_err(SnapdRequestError::Decode)?,
+        };
+        if !result.removed.is_empty() {
+            let aliases = self.get(&GetAliases).await?;
+            (result.disabled, result.removed) = result.removed.into_iter().partition(|removed| {
+                aliases
+                    .snaps
+                    .get(&removed.snap)
+                    .and_then(|snap_aliases| snap_aliases.get(&removed.alias))
+                    .is_some_and(|info| matches!(info.status, AliasStatus::Disabled { .. }))
+            });
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{decode_result, SyncResponse};
+
+    #[test]
+    fn aliases_round_trip() {
+        let aliases: Aliases = [
+            (
+                SnapName::new("lxd"),
+                SnapAlias::new("lxc"),
+                AliasInfo {
+                    command: SnapCommand::new("lxd".into(), "lxc".into()),
+                    status: AliasStatus::Auto { auto: "lxc".into() },
+                },
+            ),
+            (
+                SnapName::new("vlc"),
+                SnapAlias::new("cvlc"),
+                AliasInfo {
+                    command: SnapCommand::new("vlc".into(), "cvlc".into()),
+                    status: AliasStatus::Manual {
+                        manual: "cvlc".into(),
+                        auto: None,
+                    },
+                },
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let fixture: SyncResponse<Value> =
+            serde_json::from_str(fixture!("get_aliases.json")).unwrap();
+        assert_eq!(serde_json::to_value(&aliases).unwrap(), fixture.result);
+        assert_eq!(
+            decode_result::<Aliases>(fixture!("get_aliases.json")),
+            aliases
+        );
+    }
+
+    #[test]
+    fn disabled_aliases_may_name_their_app() {
+        let aliases: Aliases = decode_result(fixture!("get_aliases_disabled.json"));
+        let hello = &aliases.snaps[&SnapName::new("hello")];
+
+        let with_app = &hello[&SnapAlias::new("hi")];
+        assert_eq!(with_app.command, SnapCommand::name_only("hello".into()));
+        assert_eq!(
+            with_app.status,
+            AliasStatus::Disabled {
+                auto: Some("hello".into())
+            }
+        );
+
+        let without_app = &hello[&SnapAlias::new("hey")];
+        assert_eq!(
+            without_app.command,
+            SnapCommand::new("hello".into(), "universe".into())
+        );
+        assert_eq!(without_app.status, AliasStatus::Disabled { auto: None });
+    }
+
+    #[test]
+    fn prefer_resets_a_snaps_aliases() {
+        let prefer = AliasCommand::Prefer {
+            snap: "hello".into(),
+        };
+        assert_eq!(
+            serde_json::to_value(prefer.body()).unwrap(),
+            serde_json::json!({"action": "prefer", "snap": "hello"})
+        );
+
+        let unalias_all = AliasCommand::UnaliasAll {
+            snap: "hello".into(),
+        };
+        assert_eq!(
+            serde_json::to_value(unalias_all.body()).unwrap(),
+            serde_json::json!({"action": "unalias", "snap": "hello"})
+        );
+    }
+
+    #[test]
+    fn dotless_command_runs_the_snaps_own_app() {
+        let info: AliasInfo =
+            serde_json::from_str(r#"{"command": "foo", "status": "auto", "auto": "foo"}"#).unwrap();
+
+        assert_eq!(info.command, SnapCommand::name_only("foo".into()));
+        assert_eq!(info.command.app, None);
+        assert_eq!(info.status, AliasStatus::Auto { auto: "foo".into() });
+    }
+
+    #[test]
+    fn valid_aliases() {
+        for alias in ["lxc", "hello-world", "7zip", "vlc.cvlc", "python3_10", "A"] {
+            assert_eq!(SnapAlias::try_new(alias).unwrap().as_str(), alias);
+        }
+    }
+
+    #[test]
+    fn invalid_aliases() {
+        for alias in [
+            "",
+            "-lxc",
+            ".hidden",
+            "_x",
+            "two words",
+            "rm;ls",
+            "päck",
+            "a/b",
+        ] {
+            assert!(SnapAlias::try_new(alias).is_err(), "{alias:?}");
+        }
+    }
+
+    #[test]
+    fn alias_change_result_is_read_from_change_data() {
+        let change: Change = decode_result(fixture!("change_prefer_aliases.json"));
+        let result = AliasChangeResult::from_change(&change).unwrap();
+
+        let alias = |snap: &'static str, app: &'static str, alias: &'static str| AliasChange {
+            snap: snap.into(),
+            app: app.into(),
+            alias: SnapAlias::new(alias),
+        };
+        assert_eq!(
+            result,
+            AliasChangeResult {
+                added: vec![alias("vlc", "cvlc", "cvlc"), alias("vlc", "vlc", "vlc")],
+                removed: vec![alias("old-vlc", "vlc", "vlc")],
+                disabled: Vec::new(),
+            }
+        );
+
+        let nothing: Change = decode_result(fixture!("abort_change.json"));
+        assert_eq!(
+            AliasChangeResult::from_change(&nothing).unwrap(),
+            AliasChangeResult::default()
+        );
+    }
+}
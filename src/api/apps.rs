@@ -0,0 +1,97 @@
+//! The apps and services snaps provide, via `/v2/apps`.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    snap::{App, SnapName},
+    Get,
+};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// An app or service provided by an installed snap.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AppInfo<'a> {
+    pub snap: SnapName<'a>,
+    pub name: App<'a>,
+    #[serde(default)]
+    pub desktop_file: Option<Cow<'a, str>>,
+    /// The kind of service this is, such as `simple` or `forking`, or `None` for an app that
+    /// isn't a service.
+    #[serde(default)]
+    pub daemon: Option<Cow<'a, str>>,
+    /// Whether the service runs as a system or user service.
+    #[serde(default)]
+    pub daemon_scope: Option<Cow<'a, str>>,
+    /// Whether the service starts at boot.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Whether the service is running.
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub common_id: Option<Cow<'a, str>>,
+}
+
+impl AppInfo<'_> {
+    pub fn is_service(&self) -> bool {
+        self.daemon.is_some()
+    }
+}
+
+/// Lists the apps of installed snaps, via `/v2/apps`.
+#[derive(Clone, Debug, Default)]
+pub struct GetApps<'a> {
+    /// The snaps, written `snap`, or individual apps, written `snap.app`, to list. All are listed
+    /// if this is empty.
+    pub names: Vec<Cow<'a, str>>,
+    /// Whether to only list services.
+    pub services_only: bool,
+}
+
+impl Get for GetApps<'_> {
+    type Payload = Vec<AppInfo<'static>>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/apps");
+        {
+            let mut query = base_url.query_pairs_mut();
+            if !self.names.is_empty() {
+                query.append_pair("names", &self.names.join(","));
+            }
+            if self.services_only {
+                query.append_pair("select", "service");
+            }
+        }
+        if base_url.query() == Some("") {
+            base_url.set_query(None);
+        }
+        base_url
+    }
+}
+
+impl SnapdClient {
+    /// Whether any of the services of the snap `name` are running.
+    ///
+    /// `snapd` only tracks whether services are running, so this can't tell if one of the snap's
+    /// other apps is open. A snap without services is never considered running.
+    pub async fn is_running(&self, name: SnapName<'_>) -> Result<bool, SnapdRequestError> {
+        let request = GetApps {
+            names: vec![Cow::Borrowed(name.as_str())],
+            services_only: true,
+        };
+        match self.get(&request).await {
+            Ok(apps) => Ok(apps.iter().any(|app| app.active)),
+            // `snapd` reports a snap without services as not found, when only asked for services.
+            Err(SnapdRequestError::Snapd { error, .. })
+                if error.kind.as_deref() == Some("app-not-found") =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
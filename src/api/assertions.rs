@@ -0,0 +1,754 @@
+//! Signed statements from the store and brand, via `/v2/assertions`.
+//!
+//! Assertions aren't JSON: `snapd` sends them in their signed text form, made of `name: value`
+//! headers, an optional body, and a signature, each separated by a blank line. Newer versions can
+//! send their headers and body as JSON instead, without the signature, when asked with
+//! `json=true`. Older versions ignore that and send the text form, so both have to be understood.
+
+use std::{borrow::Cow, collections::BTreeMap, str::Utf8Error};
+
+use hyper::body::Bytes;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+use url::Url;
+
+use super::{
+    snap::{SnapId, SnapName},
+    time::Timestamp,
+    SyncResponse,
+};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// An endpoint that responds with assertions rather than JSON.
+pub trait GetAssertions {
+    /// The assertions the response is decoded into.
+    type Payload;
+
+    /// Points `base_url` at this endpoint, setting its path and query.
+    fn url(&self, base_url: Url) -> Url;
+
+    /// Decodes the body of `snapd`'s response.
+    fn parse(body: &[u8]) -> Result<Self::Payload, AssertionParseError>;
+}
+
+/// The ways an assertion can be malformed.
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
+pub enum AssertionParseError {
+    #[error("assertion is not valid UTF-8: {0}")]
+    Utf8(#[from] Utf8Error),
+    #[error("assertion ends before its signature")]
+    Truncated,
+    #[error("malformed assertion header {0:?}")]
+    MalformedHeader(String),
+    #[error("assertion has no {0:?} header")]
+    MissingHeader(&'static str),
+    #[error("invalid value {value:?} for assertion header {name:?}")]
+    InvalidHeader { name: &'static str, value: String },
+    #[error("expected a {expected} assertion, found {found}")]
+    UnexpectedType {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("malformed JSON assertions: {0}")]
+    Json(String),
+}
+
+/// A single assertion in the JSON form `snapd` sends when asked with `json=true`, which has no
+/// signature.
+#[derive(Clone, Debug, Deserialize)]
+pub struct JsonAssertion<'a> {
+    pub headers: Map<String, Value>,
+    /// The body, for assertion types that have one.
+    #[serde(default, borrow)]
+    pub body: Option<Cow<'a, str>>,
+}
+
+impl JsonAssertion<'_> {
+    /// The value of the header `name`, if it's a single string rather than a list or map.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(Value::as_str)
+    }
+
+    /// Parses the body of a response to a request with `json=true`, or `None` if `body` is in the
+    /// text form because `snapd` is too old to send JSON.
+    pub fn parse_response(
+        body: &[u8],
+    ) -> Option<Result<Vec<JsonAssertion<'_>>, AssertionParseError>> {
+        if !body.trim_ascii_start().starts_with(b"{") {
+            return None;
+        }
+        Some(
+            serde_json::from_slice::<SyncResponse<_>>(body)
+                .map(|response| response.result)
+                .map_err(|e| AssertionParseError::Json(e.to_string())),
+        )
+    }
+}
+
+/// A single assertion, borrowing from the text it was parsed from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Assertion<'a> {
+    headers: Vec<(&'a str, &'a str)>,
+    /// The body, for assertion types that have one.
+    pub body: Option<&'a str>,
+    /// The signature, still encoded and wrapped over lines as it was sent.
+    pub signature: &'a str,
+}
+
+impl<'a> Assertion<'a> {
+    /// Parses the first assertion in `text`, returning it and whatever follows it.
+    pub fn parse(text: &'a str) -> Result<(Self, &'a str), AssertionParseError> {
+        let (header_text, rest) = text
+            .split_once("\n\n")
+            .ok_or(AssertionParseError::Truncated)?;
+        let headers = parse_headers(header_text)?;
+
+        let body_length = match lookup(&headers, "body-length") {
+            Some(value) => value
+                .parse()
+                .map_err(|_| AssertionParseError::InvalidHeader {
+                    name: "body-length",
+                    value: value.to_owned(),
+                })?,
+            None => 0,
+        };
+
+        let (body, rest) = if body_length == 0 {
+            (None, rest)
+        } else {
+            let body = rest
+                .get(..body_length)
+                .ok_or(AssertionParseError::Truncated)?;
+            let rest = rest[body_length..]
+                .strip_prefix("\n\n")
+                .ok_or(AssertionParseError::Truncated)?;
+            (Some(body), rest)
+        };
+
+        // The signature is wrapped over as many lines as it takes, up to the blank line before the
+        // next assertion.
+        let (signature, rest) = rest.split_once("\n\n").unwrap_or((rest, ""));
+        let signature = signature.trim_end_matches('\n');
+        if signature.is_empty() {
+            return Err(AssertionParseError::Truncated);
+        }
+
+        let assertion = Self {
+            headers,
+            body,
+            signature,
+        };
+        Ok((assertion, rest.trim_start_matches('\n')))
+    }
+
+    /// The value of the header `name`.
+    ///
+    /// Values spanning several lines, such as lists and maps, are returned as they appear in the
+    /// assertion, indentation included.
+    pub fn header(&self, name: &str) -> Option<&'a str> {
+        lookup(&self.headers, name)
+    }
+
+    /// Every header, in the order they appear.
+    pub fn headers(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.headers.iter().copied()
+    }
+
+    fn require(&self, name: &'static str) -> Result<&'a str, AssertionParseError> {
+        self.header(name)
+            .ok_or(AssertionParseError::MissingHeader(name))
+    }
+
+    fn expect_type(&self, expected: &'static str) -> Result<(), AssertionParseError> {
+        match self.require("type")? {
+            found if found == expected => Ok(()),
+            found => Err(AssertionParseError::UnexpectedType {
+                expected,
+                found: found.to_owned(),
+            }),
+        }
+    }
+}
+
+fn lookup<'a>(headers: &[(&'a str, &'a str)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| *header == name)
+        .map(|(_, value)| *value)
+}
+
+/// Splits `text` into headers, keeping any indented lines following a header as its value.
+fn parse_headers(text: &str) -> Result<Vec<(&str, &str)>, AssertionParseError> {
+    let mut headers = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let (line, after) = rest.split_once('\n').unwrap_or((rest, ""));
+        let (name, value) = line
+            .split_once(':')
+            .filter(|(name, _)| !name.is_empty() && !name.starts_with(' '))
+            .ok_or_else(|| AssertionParseError::MalformedHeader(line.to_owned()))?;
+        let value = value.strip_prefix(' ').unwrap_or(value);
+
+        if !value.is_empty() {
+            headers.push((name, value));
+            rest = after;
+            continue;
+        }
+
+        // The value is on the indented lines that follow.
+        let mut end = 0;
+        for continuation in after.split_inclusive('\n') {
+            if !continuation.starts_with(' ') {
+                break;
+            }
+            end += continuation.len();
+        }
+        headers.push((name, after[..end].trim_end_matches('\n')));
+        rest = &after[end..];
+    }
+
+    Ok(headers)
+}
+
+/// A header's value, with the structure of multi-line values parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeaderValue<'a> {
+    /// A single-line value, or text spanning several lines, indentation included.
+    String(&'a str),
+    List(Vec<HeaderValue<'a>>),
+    /// A map, with its entries in the order they appear.
+    Map(Vec<(&'a str, HeaderValue<'a>)>),
+}
+
+impl<'a> HeaderValue<'a> {
+    /// Parses a value as returned by [`Assertion::header`].
+    pub fn parse(value: &'a str) -> Result<Self, AssertionParseError> {
+        if value.starts_with(' ') {
+            parse_block(value, indentation(value))
+        } else {
+            Ok(Self::String(value))
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value of the entry `key`, if this is a map with one.
+    pub fn get(&self, key: &str) -> Option<&HeaderValue<'a>> {
+        match self {
+            Self::Map(entries) => entries
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Parses a block of lines indented by `indent` spaces, with any deeper lines belonging to the line
+/// before them.
+fn parse_block<'a>(block: &'a str, indent: usize) -> Result<HeaderValue<'a>, AssertionParseError> {
+    let malformed = |line: &str| AssertionParseError::MalformedHeader(line.to_owned());
+
+    let first = &block[indent..block.find('\n').unwrap_or(block.len())];
+    let is_list = first.starts_with('-');
+    if !is_list && !first.contains(':') {
+        return Ok(HeaderValue::String(block));
+    }
+
+    let mut items = Vec::new();
+    let mut entries = Vec::new();
+    let mut rest = block;
+
+    while !rest.is_empty() {
+        let (line, after) = rest.split_once('\n').unwrap_or((rest, ""));
+        if indentation(line) != indent {
+            return Err(malformed(line));
+        }
+        let content = &line[indent..];
+
+        let nested_len: usize = after
+            .split_inclusive('\n')
+            .take_while(|next| indentation(next) > indent)
+            .map(str::len)
+            .sum();
+        let nested = after[..nested_len].trim_end_matches('\n');
+        rest = &after[nested_len..];
+
+        let value = |inline: &'a str| -> Result<HeaderValue<'a>, AssertionParseError> {
+            match (inline, nested) {
+                ("", "") => Err(malformed(line)),
+                ("", nested) => parse_block(nested, indentation(nested)),
+                (inline, "") => Ok(HeaderValue::String(inline)),
+                _ => Err(malformed(line)),
+            }
+        };
+
+        if is_list {
+            let item = content.strip_prefix('-').ok_or_else(|| malformed(line))?;
+            items.push(value(item.strip_prefix(' ').unwrap_or(item))?);
+        } else {
+            let (key, item) = content.split_once(':').ok_or_else(|| malformed(line))?;
+            entries.push((key, value(item.strip_prefix(' ').unwrap_or(item))?));
+        }
+    }
+
+    Ok(if is_list {
+        HeaderValue::List(items)
+    } else {
+        HeaderValue::Map(entries)
+    })
+}
+
+/// A snap's declaration, which ties its ID to its name and publisher.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapDeclaration<'a> {
+    pub snap_id: SnapId<'a>,
+    pub snap_name: SnapName<'a>,
+    /// The account ID of the snap's publisher.
+    pub publisher_id: Cow<'a, str>,
+    pub series: Cow<'a, str>,
+    pub timestamp: Timestamp,
+}
+
+impl<'a> SnapDeclaration<'a> {
+    /// Reads a declaration from a parsed assertion.
+    pub fn from_assertion(assertion: &Assertion<'a>) -> Result<Self, AssertionParseError> {
+        assertion.expect_type("snap-declaration")?;
+        Self::from_headers(|name| assertion.header(name))
+    }
+
+    /// Reads a declaration from an assertion in JSON form.
+    pub fn from_json(assertion: &'a JsonAssertion<'_>) -> Result<Self, AssertionParseError> {
+        match assertion.header("type") {
+            Some("snap-declaration") => Self::from_headers(|name| assertion.header(name)),
+            found => Err(AssertionParseError::UnexpectedType {
+                expected: "snap-declaration",
+                found: found.unwrap_or_default().to_owned(),
+            }),
+        }
+    }
+
+    fn from_headers(
+        header: impl Fn(&'static str) -> Option<&'a str>,
+    ) -> Result<Self, AssertionParseError> {
+        let require = |name| header(name).ok_or(AssertionParseError::MissingHeader(name));
+
+        let timestamp = require("timestamp")?;
+        Ok(Self {
+            snap_id: require("snap-id")?.into(),
+            snap_name: require("snap-name")?.into(),
+            publisher_id: require("publisher-id")?.into(),
+            series: require("series")?.into(),
+            timestamp: timestamp
+                .parse()
+                .map_err(|_| AssertionParseError::InvalidHeader {
+                    name: "timestamp",
+                    value: timestamp.to_owned(),
+                })?,
+        })
+    }
+
+    pub fn into_owned(self) -> SnapDeclaration<'static> {
+        SnapDeclaration {
+            snap_id: self.snap_id.into_owned(),
+            snap_name: self.snap_name.into_owned(),
+            publisher_id: Cow::Owned(self.publisher_id.into_owned()),
+            series: Cow::Owned(self.series.into_owned()),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+/// The response to a query for snap declarations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeclarationAssertionPayload<'a> {
+    /// The declarations found, in the order `snapd` sent them.
+    pub declarations: Vec<SnapDeclaration<'a>>,
+}
+
+impl<'a> DeclarationAssertionPayload<'a> {
+    /// Parses the body of a response from `/v2/assertions/snap-declaration`, in either form.
+    pub fn try_parse(body: &'a [u8]) -> Result<Self, AssertionParseError> {
+        if let Some(assertions) = JsonAssertion::parse_response(body) {
+            let declarations = assertions?
+                .iter()
+                .map(|assertion| {
+                    SnapDeclaration::from_json(assertion).map(SnapDeclaration::into_owned)
+                })
+                .collect::<Result<_, _>>()?;
+            return Ok(Self { declarations });
+        }
+
+        let mut rest = std::str::from_utf8(body)?.trim_start_matches('\n');
+        let mut declarations = Vec::new();
+
+        while !rest.is_empty() {
+            let (assertion, after) = Assertion::parse(rest)?;
+            declarations.push(SnapDeclaration::from_assertion(&assertion)?);
+            rest = after;
+        }
+
+        Ok(Self { declarations })
+    }
+
+    pub fn into_owned(self) -> DeclarationAssertionPayload<'static> {
+        DeclarationAssertionPayload {
+            declarations: self
+                .declarations
+                .into_iter()
+                .map(SnapDeclaration::into_owned)
+                .collect(),
+        }
+    }
+}
+
+/// Gets the declaration of the snap with ID `snap_id`, via `/v2/assertions/snap-declaration`.
+///
+/// Only declarations already in `snapd`'s database, such as those of installed snaps, are found
+/// unless `remote` is set, in which case the store is asked.
+#[derive(Clone, Debug)]
+pub struct GetSnapDeclaration<'a> {
+    pub snap_id: SnapId<'a>,
+    pub remote: bool,
+}
+
+impl GetAssertions for GetSnapDeclaration<'_> {
+    type Payload = DeclarationAssertionPayload<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/assertions/snap-declaration");
+        base_url
+            .query_pairs_mut()
+            .append_pair("snap-id", self.snap_id.as_str())
+            .append_pair("json", "true");
+        if self.remote {
+            // The store is only asked for assertions whose primary key is given in full.
+            base_url
+                .query_pairs_mut()
+                .append_pair("series", "16")
+                .append_pair("remote", "true");
+        }
+        base_url
+    }
+
+    fn parse(body: &[u8]) -> Result<Self::Payload, AssertionParseError> {
+        DeclarationAssertionPayload::try_parse(body).map(DeclarationAssertionPayload::into_owned)
+    }
+}
+
+impl SnapdClient {
+    /// Gets the assertions of type `assertion_type` whose headers match `filters`, such as
+    /// `[("snap-id", id)]`, in their signed text form, without parsing them.
+    ///
+    /// This allows fetching assertion types that aren't modelled here.
+    pub async fn assertions_raw(
+        &self,
+        assertion_type: &str,
+        filters: &[(&str, &str)],
+    ) -> Result<Bytes, SnapdRequestError> {
+        let mut url = self.base_url().clone();
+        url.path_segments_mut()
+            .map_err(|()| SnapdRequestError::InvalidBaseUrl(self.base_url().clone()))?
+            .clear()
+            .extend(["v2", "assertions", assertion_type]);
+        url.query_pairs_mut().extend_pairs(filters);
+        if url.query() == Some("") {
+            url.set_query(None);
+        }
+        self.get_bytes(&url).await
+    }
+}
+
+/// The ways looking up a snap's name from its ID can fail.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SnapNameFromIdError {
+    #[error(transparent)]
+    Request(#[from] SnapdRequestError),
+    #[error("no snap with ID {0} was found")]
+    NoSnapsFound(SnapId<'static>),
+}
+
+/// Looks up a snap's name from its ID, using the store's declaration of the snap.
+#[derive(Clone, Copy, Debug)]
+pub struct SnapNameFromId;
+
+impl SnapNameFromId {
+    pub async fn get_name(
+        id: SnapId<'_>,
+        client: &SnapdClient,
+    ) -> Result<SnapName<'static>, SnapNameFromIdError> {
+        let cache = client.lookup_cache();
+        if let Some(name) = cache.and_then(|cache| cache.name(&id)) {
+            return Ok(name);
+        }
+
+        let payload = client
+            .get_assertions(&GetSnapDeclaration {
+                snap_id: id.clone(),
+                remote: true,
+            })
+            .await?;
+
+        let declaration = payload
+            .declarations
+            .into_iter()
+            .next()
+            .ok_or_else(|| SnapNameFromIdError::NoSnapsFound(id.into_owned()))?;
+        if let Some(cache) = cache {
+            cache.insert(&declaration.snap_id, &declaration.snap_name);
+        }
+        Ok(declaration.snap_name)
+    }
+}
+
+/// The default policy for installing snaps with, and connecting, each interface, from the base
+/// declaration.
+///
+/// Snap declarations can override this policy for individual snaps.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BaseDeclarationPolicy<'a> {
+    /// The policy for plugs of each interface.
+    pub plugs: BTreeMap<&'a str, InterfacePolicy<'a>>,
+    /// The policy for slots of each interface.
+    pub slots: BTreeMap<&'a str, InterfacePolicy<'a>>,
+}
+
+impl<'a> BaseDeclarationPolicy<'a> {
+    /// Parses the base declaration's text.
+    ///
+    /// The base declaration is built into `snapd` rather than signed, so any signature is ignored.
+    pub fn parse(text: &'a str) -> Result<Self, AssertionParseError> {
+        let header_text = text.split_once("\n\n").map_or(text, |(headers, _)| headers);
+        let headers = parse_headers(header_text.trim_end_matches('\n'))?;
+
+        match lookup(&headers, "type") {
+            Some("base-declaration") => {}
+            Some(found) => {
+                return Err(AssertionParseError::UnexpectedType {
+                    expected: "base-declaration",
+                    found: found.to_owned(),
+                })
+            }
+            None => return Err(AssertionParseError::MissingHeader("type")),
+        }
+
+        Ok(Self {
+            plugs: interface_policies(&headers, "plugs")?,
+            slots: interface_policies(&headers, "slots")?,
+        })
+    }
+}
+
+fn interface_policies<'a>(
+    headers: &[(&'a str, &'a str)],
+    name: &'static str,
+) -> Result<BTreeMap<&'a str, InterfacePolicy<'a>>, AssertionParseError> {
+    let invalid = |value: &str| AssertionParseError::InvalidHeader {
+        name,
+        value: value.to_owned(),
+    };
+
+    let Some(raw) = lookup(headers, name) else {
+        return Ok(BTreeMap::new());
+    };
+    let HeaderValue::Map(interfaces) = HeaderValue::parse(raw)? else {
+        return Err(invalid(raw));
+    };
+
+    interfaces
+        .into_iter()
+        .map(|(interface, rules)| match rules {
+            HeaderValue::Map(rules) => Ok((interface, InterfacePolicy::from_rules(rules))),
+            // An interface can be listed with no rules, as `interface: true`.
+            HeaderValue::String(_) => Ok((interface, InterfacePolicy::default())),
+            HeaderValue::List(_) => Err(invalid(raw)),
+        })
+        .collect()
+}
+
+/// The rules governing an interface's plugs or slots.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InterfacePolicy<'a> {
+    pub allow_installation: Option<PolicyRule<'a>>,
+    pub deny_installation: Option<PolicyRule<'a>>,
+    pub allow_connection: Option<PolicyRule<'a>>,
+    pub deny_connection: Option<PolicyRule<'a>>,
+    pub allow_auto_connection: Option<PolicyRule<'a>>,
+    pub deny_auto_connection: Option<PolicyRule<'a>>,
+}
+
+impl<'a> InterfacePolicy<'a> {
+    fn from_rules(rules: Vec<(&'a str, HeaderValue<'a>)>) -> Self {
+        let mut policy = Self::default();
+        for (name, rule) in rules {
+            let slot = match name {
+                "allow-installation" => &mut policy.allow_installation,
+                "deny-installation" => &mut policy.deny_installation,
+                "allow-connection" => &mut policy.allow_connection,
+                "deny-connection" => &mut policy.deny_connection,
+                "allow-auto-connection" => &mut policy.allow_auto_connection,
+                "deny-auto-connection" => &mut policy.deny_auto_connection,
+                _ => continue,
+            };
+            *slot = Some(PolicyRule::from(rule));
+        }
+        policy
+    }
+}
+
+/// A single rule of an [`InterfacePolicy`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyRule<'a> {
+    /// The rule always or never applies.
+    Bool(bool),
+    /// The rule applies when these constraints, such as `on-classic` or `slot-snap-type`, are met.
+    Constraints(HeaderValue<'a>),
+}
+
+impl<'a> From<HeaderValue<'a>> for PolicyRule<'a> {
+    fn from(value: HeaderValue<'a>) -> Self {
+        match value {
+            HeaderValue::String("true") => Self::Bool(true),
+            HeaderValue::String("false") => Self::Bool(false),
+            constraints => Self::Constraints(constraints),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_declarations_are_errors() {
+        assert!(matches!(
+            DeclarationAssertionPayload::try_parse(b"type: snap-declaration\n\xff\xfe\n\nsig\n"),
+            Err(AssertionParseError::Utf8(_))
+        ));
+        // Cut off before the blank line ending the headers.
+        assert!(matches!(
+            DeclarationAssertionPayload::try_parse(b"type: snap-declaration\nsnap-id: abc"),
+            Err(AssertionParseError::Truncated)
+        ));
+        // Cut off before the signature.
+        assert!(matches!(
+            DeclarationAssertionPayload::try_parse(b"type: snap-declaration\nsnap-id: abc\n\n"),
+            Err(AssertionParseError::Truncated)
+        ));
+        assert!(matches!(
+            DeclarationAssertionPayload::try_parse(b"type snap-declaration\n\nsig\n"),
+            Err(AssertionParseError::MalformedHeader(header)) if header == "type snap-declaration"
+        ));
+        assert!(matches!(
+            DeclarationAssertionPayload::try_parse(b"type: snap-declaration\n\nsig\n"),
+            Err(AssertionParseError::MissingHeader("timestamp"))
+        ));
+    }
+
+    #[test]
+    fn every_declaration_is_parsed() {
+        let payload =
+            DeclarationAssertionPayload::try_parse(fixture!("snap_declarations.txt").as_bytes())
+                .unwrap();
+
+        let names: Vec<_> = payload
+            .declarations
+            .iter()
+            .map(|declaration| (declaration.snap_id.as_str(), declaration.snap_name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("buPKUD3TKqCOgLEjjHx5kSiCpIs5cMuQ", "hello-world"),
+                ("3wdHCAVyZEmYsCMFDE9qt92UV8rC8Wdk", "firefox"),
+            ]
+        );
+        assert_eq!(
+            payload.declarations[1].publisher_id,
+            "OgeoZuqQpVvSr9eGKJzNCrFGSaKXpkey"
+        );
+    }
+
+    #[test]
+    fn wrapped_signatures_are_read_whole() {
+        let text = fixture!("snap_declarations.txt");
+        let (first, rest) = Assertion::parse(text).unwrap();
+        let (second, rest) = Assertion::parse(rest).unwrap();
+
+        assert!(first.signature.starts_with("AcLBUgQAAQoABgUCV3p4OQAA"));
+        assert!(first.signature.lines().count() > 1);
+        assert!(!first.signature.ends_with('\n'));
+        assert_eq!(second.header("snap-name"), Some("firefox"));
+        assert!(second.signature.lines().count() > 1);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn base_declaration_rules_are_read() {
+        let policy = BaseDeclarationPolicy::parse(fixture!("base_declaration.txt")).unwrap();
+
+        let home = &policy.slots["home"];
+        assert_eq!(
+            home.deny_auto_connection,
+            Some(PolicyRule::Constraints(HeaderValue::Map(vec![(
+                "on-classic",
+                HeaderValue::String("false")
+            )])))
+        );
+        assert_eq!(
+            home.allow_installation
+                .as_ref()
+                .and_then(|rule| match rule {
+                    PolicyRule::Constraints(constraints) => constraints.get("slot-snap-type"),
+                    PolicyRule::Bool(_) => None,
+                }),
+            Some(&HeaderValue::List(vec![HeaderValue::String("core")]))
+        );
+        assert_eq!(home.allow_auto_connection, None);
+        assert_eq!(
+            policy.plugs["snapd-control"].allow_installation,
+            Some(PolicyRule::Bool(false))
+        );
+    }
+
+    #[test]
+    fn json_declaration_is_parsed() {
+        let payload =
+            DeclarationAssertionPayload::try_parse(fixture!("snap_declaration.json").as_bytes())
+                .unwrap();
+
+        assert_eq!(
+            payload.declarations,
+            [SnapDeclaration {
+                snap_id: "3wdHCAVyZEmYsCMFDE9qt92UV8rC8Wdk".into(),
+                snap_name: "firefox".into(),
+                publisher_id: "OgeoZuqQpVvSr9eGKJzNCrFGSaKXpkey".into(),
+                series: "16".into(),
+                timestamp: "2021-09-02T12:34:56.789012Z".parse().unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn json_assertion_of_another_type_is_an_error() {
+        let body = br#"{"type": "sync", "status-code": 200, "status": "OK",
+            "result": [{"headers": {"type": "account", "account-id": "canonical"}}]}"#;
+        assert!(matches!(
+            DeclarationAssertionPayload::try_parse(body),
+            Err(AssertionParseError::UnexpectedType { found, .. }) if found == "account"
+        ));
+    }
+}
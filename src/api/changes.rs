@@ -0,0 +1,512 @@
+//! Tracking asynchronous operations, via `/v2/changes`.
+
+use std::{borrow::Cow, cmp::Reverse, collections::HashMap, fmt, time::Duration};
+
+use hyper::StatusCode;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{sync::mpsc, time::Instant};
+use url::Url;
+
+use super::{snap::SnapName, snapshots::SnapshotSetId, time::Timestamp, Get, Post, SyncResponse};
+use crate::{client::join_requests, SnapdClient, SnapdRequestError};
+
+/// How long [`SnapdClient::wait_for_change`] waits between checks on a change.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The ID of a change, which `snapd` returns when it starts an asynchronous operation.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ChangeId(String);
+
+impl ChangeId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for ChangeId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl From<&str> for ChangeId {
+    fn from(id: &str) -> Self {
+        Self::new(id)
+    }
+}
+
+impl fmt::Display for ChangeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// An operation `snapd` is carrying out, or has carried out, made of one or more tasks.
+///
+/// With the `strict-decode` feature, unknown fields are rejected.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "strict-decode", serde(deny_unknown_fields))]
+pub struct Change<'a> {
+    pub id: ChangeId,
+    /// What the change does, such as `install-snap`. This is kept as a string, as `snapd` adds
+    /// new kinds of change often.
+    pub kind: Cow<'a, str>,
+    pub summary: Cow<'a, str>,
+    pub status: ChangeStatus,
+    #[serde(default)]
+    pub tasks: Vec<Task<'a>>,
+    /// Whether the change has finished, successfully or not.
+    pub ready: bool,
+    /// Why the change failed, if it did.
+    #[serde(default)]
+    pub err: Option<Cow<'a, str>>,
+    pub spawn_time: Timestamp,
+    #[serde(default)]
+    pub ready_time: Option<Timestamp>,
+    /// Results of the change, whose shape depends on its [`kind`](Self::kind).
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub data: Value,
+}
+
+impl Change<'_> {
+    /// The snaps the change affects, from its `snap-names` data, as set by changes that install,
+    /// refresh, or remove several snaps, or save or restore snapshots.
+    ///
+    /// This is empty for changes that don't record which snaps they affect.
+    pub fn affected_snaps(&self) -> Vec<SnapName<'_>> {
+        self.data
+            .get("snap-names")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(SnapName::new)
+            .collect()
+    }
+
+    /// The ID of the snapshot set a snapshot change saved or restored, from its `set-id` data.
+    pub fn snapshot_set_id(&self) -> Option<SnapshotSetId> {
+        self.data
+            .get("set-id")
+            .and_then(Value::as_u64)
+            .map(SnapshotSetId)
+    }
+}
+
+/// A single step of a [`Change`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Task<'a> {
+    pub id: Cow<'a, str>,
+    /// What the task does, such as `download-snap`. Like a change's kind, this is kept as a string.
+    pub kind: Cow<'a, str>,
+    pub summary: Cow<'a, str>,
+    pub status: ChangeStatus,
+    #[serde(default)]
+    pub log: Vec<Cow<'a, str>>,
+    pub progress: Progress<'a>,
+    pub spawn_time: Timestamp,
+    #[serde(default)]
+    pub ready_time: Option<Timestamp>,
+}
+
+/// How far through its work a [`Task`] is.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Progress<'a> {
+    #[serde(default)]
+    pub label: Cow<'a, str>,
+    pub done: u64,
+    pub total: u64,
+}
+
+/// The state of a [`Change`] or [`Task`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChangeStatus {
+    Default,
+    Hold,
+    Do,
+    Doing,
+    Done,
+    Abort,
+    Undo,
+    Undoing,
+    Undone,
+    Error,
+    Wait,
+    /// A state this crate doesn't know about, as `snapd` named it.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl ChangeStatus {
+    /// Whether this is a final state, which the change or task won't leave.
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Self::Hold | Self::Done | Self::Undone | Self::Error)
+    }
+}
+
+/// Gets a single change, via `/v2/changes/{id}`.
+#[derive(Clone, Copy, Debug)]
+pub struct GetChange<'a> {
+    pub id: &'a ChangeId,
+}
+
+impl Get for GetChange<'_> {
+    type Payload = Change<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/changes/{}", self.id));
+        base_url
+    }
+}
+
+/// Which changes [`GetChanges`] lists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ChangeSelect {
+    /// Changes that aren't ready yet.
+    #[default]
+    InProgress,
+    /// Changes that are ready, successfully or not.
+    Ready,
+    All,
+}
+
+impl ChangeSelect {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::InProgress => "in-progress",
+            Self::Ready => "ready",
+            Self::All => "all",
+        }
+    }
+}
+
+/// Lists changes, via `/v2/changes`.
+///
+/// `snapd` forgets changes some time after they're ready, so only recent ones are listed.
+#[derive(Clone, Debug, Default)]
+pub struct GetChanges<'a> {
+    pub select: ChangeSelect,
+    /// Only lists changes affecting this snap.
+    pub snap: Option<SnapName<'a>>,
+}
+
+impl Get for GetChanges<'_> {
+    type Payload = Vec<Change<'static>>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/changes");
+        {
+            let mut query = base_url.query_pairs_mut();
+            query.append_pair("select", self.select.as_str());
+            if let Some(snap) = &self.snap {
+                query.append_pair("for", snap.as_str());
+            }
+        }
+        base_url
+    }
+}
+
+/// Aborts a change that isn't ready, via a POST to `/v2/changes/{id}`, undoing what it's done so
+/// far.
+///
+/// `snapd` responds with the change as it stands once aborted, and undoes it in the background.
+/// It refuses with a `400 Bad Request` if the change is already ready.
+#[derive(Clone, Copy, Debug)]
+pub struct AbortChange<'a> {
+    pub id: &'a ChangeId,
+}
+
+/// The body of an [`AbortChange`].
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum ChangeAction {
+    Abort,
+}
+
+impl Post for AbortChange<'_> {
+    type Body<'b>
+        = ChangeAction
+    where
+        Self: 'b;
+    type Response = SyncResponse<Change<'static>>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/changes/{}", self.id));
+        base_url
+    }
+
+    fn body(&self) -> ChangeAction {
+        ChangeAction::Abort
+    }
+}
+
+/// How far through a [`Change`] is, as reported by
+/// [`SnapdClient::wait_for_change_with_progress`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangeProgress {
+    /// The summary of the task being worked on, or of the change if none is.
+    pub summary: String,
+    /// How much of the work across all the change's tasks is done, from 0 to 100.
+    pub percent: f64,
+}
+
+impl ChangeProgress {
+    fn of(change: &Change<'_>) -> Self {
+        let summary = change
+            .tasks
+            .iter()
+            .find(|task| task.status == ChangeStatus::Doing)
+            .map_or(&change.summary, |task| &task.summary);
+        let (done, total) = change.tasks.iter().fold((0, 0), |(done, total), task| {
+            (done + task.progress.done, total + task.progress.total)
+        });
+
+        Self {
+            summary: summary.to_string(),
+            percent: if total == 0 {
+                0.0
+            } else {
+                done as f64 * 100.0 / total as f64
+            },
+        }
+    }
+}
+
+impl SnapdClient {
+    /// Lists the changes affecting the snap `name` that `snapd` remembers, whether ready or not,
+    /// most recently started first.
+    pub async fn changes_for(
+        &self,
+        name: SnapName<'_>,
+    ) -> Result<Vec<Change<'static>>, SnapdRequestError> {
+        let mut changes = self
+            .get(&GetChanges {
+                select: ChangeSelect::All,
+                snap: Some(name),
+            })
+            .await?;
+        changes.sort_by_key(|change| Reverse(change.spawn_time));
+        Ok(changes)
+    }
+
+    /// Polls the change `id` until it's ready, returning its final state.
+    ///
+    /// A change that failed is still returned successfully: check its
+    /// [`status`](Change::status) and [`err`](Change::err).
+    ///
+    /// Cancelling the client's [cancellation token](SnapdClient::with_cancellation) stops the
+    /// polling, but not the change itself.
+    pub async fn wait_for_change(
+        &self,
+        id: &ChangeId,
+    ) -> Result<Change<'static>, SnapdRequestError> {
+        self.poll_change(id, None).await
+    }
+
+    /// Polls the change `id` until it's ready, like [`wait_for_change`](Self::wait_for_change),
+    /// sending its progress to `progress` each time it's checked, e.g. to draw a progress bar.
+    ///
+    /// Every update is sent, in order, ending with the change's final state, so polling waits
+    /// while the channel is full. The change is still waited for if `progress`'s receiver is
+    /// dropped.
+    pub async fn wait_for_change_with_progress(
+        &self,
+        id: &ChangeId,
+        progress: mpsc::Sender<ChangeProgress>,
+    ) -> Result<Change<'static>, SnapdRequestError> {
+        self.poll_change(id, Some(&progress)).await
+    }
+
+    /// Polls the changes `ids` until they're all ready, returning each one's final state, or why
+    /// it couldn't be waited on, in the same order. This is like
+    /// [`wait_for_change`](Self::wait_for_change) for each, but the changes are polled
+    /// concurrently, each from its own task.
+    ///
+    /// If `timeout` is given, it covers the wait as a whole: a change that isn't ready within it
+    /// fails with [`SnapdRequestError::TimedOut`], though the change itself carries on.
+    pub async fn wait_for_changes(
+        &self,
+        ids: &[ChangeId],
+        timeout: Option<Duration>,
+    ) -> Vec<Result<Change<'static>, SnapdRequestError>> {
+        let deadline = timeout.map(|timeout| (Instant::now() + timeout, timeout));
+
+        let waits = ids.iter().map(|id| {
+            let client = self.clone();
+            let wait = {
+                let id = id.clone();
+                async move {
+                    let wait = client.wait_for_change(&id);
+                    match deadline {
+                        Some((deadline, timeout)) => tokio::time::timeout_at(deadline, wait)
+                            .await
+                            .unwrap_or(Err(SnapdRequestError::TimedOut(timeout))),
+                        None => wait.await,
+                    }
+                }
+            };
+            (id, wait)
+        });
+        join_requests(waits)
+            .await
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+
+    /// Aborts every change that isn't ready, returning the result of aborting each, by its ID.
+    ///
+    /// A change that becomes ready before it can be aborted, or that `snapd` forgets, is skipped
+    /// and left out of the results.
+    pub async fn abort_all_changes(
+        &self,
+    ) -> Result<HashMap<ChangeId, Result<Change<'static>, SnapdRequestError>>, SnapdRequestError>
+    {
+        let changes = self.get(&GetChanges::default()).await?;
+
+        let mut results = HashMap::with_capacity(changes.len());
+        for change in changes {
+            let result = self.post(&AbortChange { id: &change.id }).await;
+            if let Err(SnapdRequestError::Snapd { status, .. }) = &result {
+                // A change that's ready can't be aborted, but other bad requests are errors.
+                if (*status == StatusCode::BAD_REQUEST || *status == StatusCode::NOT_FOUND)
+                    && self.change_is_settled(&change.id).await
+                {
+                    continue;
+                }
+            }
+            results.insert(change.id, result);
+        }
+        Ok(results)
+    }
+
+    /// Whether the change `id` is ready or has been forgotten. Any error checking is taken to mean
+    /// it isn't.
+    async fn change_is_settled(&self, id: &ChangeId) -> bool {
+        match self.get(&GetChange { id }).await {
+            Ok(change) => change.ready,
+            Err(SnapdRequestError::Snapd { status, .. }) => status == StatusCode::NOT_FOUND,
+            Err(_) => false,
+        }
+    }
+
+    async fn poll_change(
+        &self,
+        id: &ChangeId,
+        progress: Option<&mpsc::Sender<ChangeProgress>>,
+    ) -> Result<Change<'static>, SnapdRequestError> {
+        self.cancellable(async {
+            loop {
+                // Some changes are ready by the time `snapd` responds with their ID, so the first
+                // check is made straight away.
+                let change = self.get(&GetChange { id }).await?;
+                if let Some(progress) = progress {
+                    // A closed channel only means nobody is watching any more.
+                    let _ = progress.send(ChangeProgress::of(&change)).await;
+                }
+                if change.ready {
+                    return Ok(change);
+                }
+
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decode_result;
+
+    #[cfg(not(feature = "strict-decode"))]
+    #[test]
+    fn unmodelled_fields_are_ignored() {
+        let change: Change = decode_result(fixture!("change_unmodelled_fields.json"));
+        assert_eq!(change.status, ChangeStatus::Done);
+    }
+
+    #[test]
+    fn changes_decode() {
+        let change: Change = decode_result(fixture!("abort_change.json"));
+        assert_eq!(change.id, ChangeId::new("42"));
+        assert!(!change.ready);
+    }
+
+    #[cfg(feature = "strict-decode")]
+    #[test]
+    fn strict_decode_rejects_unmodelled_fields() {
+        let error = serde_json::from_str::<crate::api::SyncResponse<Change>>(fixture!(
+            "change_unmodelled_fields.json"
+        ))
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field"), "{error}");
+    }
+
+    #[test]
+    fn install_data_names_the_snaps() {
+        let change: Change = decode_result(fixture!("change_install_many.json"));
+
+        assert_eq!(
+            change.affected_snaps(),
+            [SnapName::new("hello"), SnapName::new("jq")]
+        );
+        assert_eq!(change.snapshot_set_id(), None);
+    }
+
+    #[test]
+    fn snapshot_data_has_the_set_id() {
+        let change: Change = decode_result(fixture!("change_snapshot.json"));
+
+        assert_eq!(change.snapshot_set_id(), Some(SnapshotSetId(14)));
+        assert_eq!(change.affected_snaps(), [SnapName::new("firefox")]);
+
+        let change: Change = decode_result(fixture!("abort_change.json"));
+        assert!(change.affected_snaps().is_empty());
+        assert_eq!(change.snapshot_set_id(), None);
+    }
+
+    #[test]
+    fn unknown_kinds_and_statuses_are_kept() {
+        let change: Change = serde_json::from_value(serde_json::json!({
+            "id": "7",
+            "kind": "reticulate-splines",
+            "summary": "Reticulate splines",
+            "status": "Reticulating",
+            "ready": false,
+            "spawn-time": "2024-05-01T10:00:00Z",
+            "tasks": [{
+                "id": "70",
+                "kind": "align-splines",
+                "summary": "Align splines",
+                "status": "Aligning",
+                "progress": {"label": "", "done": 0, "total": 1},
+                "spawn-time": "2024-05-01T10:00:00Z",
+            }],
+        }))
+        .unwrap();
+
+        assert_eq!(change.kind, "reticulate-splines");
+        assert_eq!(change.status, ChangeStatus::Unknown("Reticulating".into()));
+        assert!(!change.status.is_ready());
+        assert_eq!(change.tasks[0].kind, "align-splines");
+        assert_eq!(
+            change.tasks[0].status,
+            ChangeStatus::Unknown("Aligning".into())
+        );
+        assert_eq!(
+            serde_json::to_value(&change.status).unwrap(),
+            "Reticulating"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Snap configuration, via `/v2/snaps/{name}/conf`.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use url::Url;
+
+use super::{
+    changes::{Change, ChangeId},
+    snap::SnapName,
+    Get, Put,
+};
+use crate::{client::join_requests, SnapdClient, SnapdRequestError};
+
+/// Gets a snap's configuration, via `/v2/snaps/{name}/conf`.
+///
+/// Keys may be dotted paths into nested configuration, such as `proxy.http`. If `keys` is empty,
+/// the snap's whole configuration is returned.
+///
+/// The result holds exactly the keys `snapd` sent, so a key that's unset is absent, while one set
+/// to `null` is present as [`Value::Null`]. If only one key is asked for and it's unset, `snapd`
+/// responds with an `option-not-found` error instead.
+///
+/// The keys are sorted, unless the `preserve-order` feature keeps them in the order `snapd` sent
+/// them.
+#[derive(Clone, Debug)]
+pub struct GetSnapConf<'a> {
+    pub name: SnapName<'a>,
+    pub keys: Vec<Cow<'a, str>>,
+}
+
+impl Get for GetSnapConf<'_> {
+    type Payload = Map<String, Value>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/snaps/{}/conf", self.name));
+        if !self.keys.is_empty() {
+            base_url
+                .query_pairs_mut()
+                .append_pair("keys", &self.keys.join(","));
+        }
+        base_url
+    }
+}
+
+/// Finds the dotted `key`, such as `proxy.http`, in configuration returned by [`GetSnapConf`].
+///
+/// `snapd` returns a dotted key as is when it's asked for, such as `{"proxy.http": ...}`, but
+/// nested when a parent key is asked for, such as `{"proxy": {"http": ...}}`. This finds the value
+/// either way.
+pub fn conf_value<'a>(conf: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    if let Some(value) = conf.get(key) {
+        return Some(value);
+    }
+    key.match_indices('.')
+        .find_map(|(dot, _)| match conf.get(&key[..dot]) {
+            Some(Value::Object(nested)) => conf_value(nested, &key[dot + 1..]),
+            _ => None,
+        })
+}
+
+/// Expands the dotted keys in configuration returned by [`GetSnapConf`] into nested objects, so
+/// that `{"proxy.http": ...}` becomes `{"proxy": {"http": ...}}`, as if the parent key had been
+/// asked for.
+pub fn nest_conf(conf: Map<String, Value>) -> Map<String, Value> {
+    let mut nested = Map::new();
+    for (key, value) in conf {
+        let mut parts = key.split('.').peekable();
+        let mut object = &mut nested;
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                object.insert(part.to_owned(), value);
+                break;
+            }
+            let child = object
+                .entry(part)
+                .or_insert_with(|| Value::Object(Map::new()));
+            // A parent key can only hold nested keys if it's an object.
+            if !child.is_object() {
+                *child = Value::Object(Map::new());
+            }
+            object = child
+                .as_object_mut()
+                .expect("the parent was made an object");
+        }
+    }
+    nested
+}
+
+/// Sets a snap's configuration, via a PUT to `/v2/snaps/{name}/conf`.
+///
+/// Keys may be dotted paths into nested configuration, such as `proxy.http`. The snap's
+/// `configure` hook runs before the change is ready, and may restart its services.
+#[derive(Clone, Debug)]
+pub struct SetSnapConf<'a> {
+    pub name: SnapName<'a>,
+    pub conf: &'a Map<String, Value>,
+}
+
+impl Put for SetSnapConf<'_> {
+    type Body<'b>
+        = &'b Map<String, Value>
+    where
+        Self: 'b;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/snaps/{}/conf", self.name));
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self.conf
+    }
+}
+
+/// Unsets a snap's configuration, via a PUT to `/v2/snaps/{name}/conf`, as `snap unset` does.
+///
+/// `snapd` unsets the keys sent with a `null` value, so each key is sent as `{"key": null}`.
+/// Like with [`SetSnapConf`], keys may be dotted paths, and unsetting a parent key unsets
+/// everything nested under it.
+#[derive(Clone, Debug)]
+pub struct UnsetSnapConf<'a> {
+    pub name: SnapName<'a>,
+    pub keys: Vec<Cow<'a, str>>,
+}
+
+impl Put for UnsetSnapConf<'_> {
+    type Body<'b>
+        = BTreeMap<&'b str, Value>
+    where
+        Self: 'b;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/snaps/{}/conf", self.name));
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self.keys.iter().map(|key| (&**key, Value::Null)).collect()
+    }
+}
+
+impl SnapdClient {
+    /// Gets the value of the dotted `key` in the configuration of the snap `name`, or `None` if
+    /// it's unset.
+    pub async fn snap_conf_value(
+        &self,
+        name: SnapName<'_>,
+        key: &str,
+    ) -> Result<Option<Value>, SnapdRequestError> {
+        let request = GetSnapConf {
+            name,
+            keys: vec![Cow::Borrowed(key)],
+        };
+        match self.get(&request).await {
+            Ok(conf) => Ok(conf_value(&conf, key).cloned()),
+            Err(SnapdRequestError::Snapd { error, .. })
+                if error.kind.as_deref() == Some("option-not-found") =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets the configuration `keys` of the snap `name`, decoded as a `T`, with dotted keys
+    /// nested as [`nest_conf`] does. If `keys` is empty, the snap's whole configuration is
+    /// decoded.
+    ///
+    /// ```no_run
+    /// # async fn f(client: snapd::SnapdClient) -> Result<(), snapd::SnapdRequestError> {
+    /// #[derive(serde::Deserialize)]
+    /// struct Proxy {
+    ///     http: String,
+    ///     https: String,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Conf {
+    ///     proxy: Proxy,
+    /// }
+    ///
+    /// let conf: Conf = client
+    ///     .get_conf_typed("core".into(), &["proxy.http", "proxy.https"])
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_conf_typed<T: DeserializeOwned>(
+        &self,
+        name: SnapName<'_>,
+        keys: &[&str],
+    ) -> Result<T, SnapdRequestError> {
+        let request = GetSnapConf {
+            name,
+            keys: keys.iter().copied().map(Cow::Borrowed).collect(),
+        };
+        let conf = nest_conf(self.get(&request).await?);
+        Ok(serde_json::from_value(Value::Object(conf))?)
+    }
+
+    /// Sets configuration on the snap `name`, returning the change applying it.
+    pub async fn set_snap_conf(
+        &self,
+        name: SnapName<'_>,
+        conf: &Map<String, Value>,
+    ) -> Result<ChangeId, SnapdRequestError> {
+        self.put(&SetSnapConf { name, conf }).await
+    }
+
+    /// Unsets the configuration `keys` of the snap `name`, returning the change doing so.
+    pub async fn unset_snap_conf(
+        &self,
+        name: SnapName<'_>,
+        keys: &[&str],
+    ) -> Result<ChangeId, SnapdRequestError> {
+        let request = UnsetSnapConf {
+            name,
+            keys: keys.iter().copied().map(Cow::Borrowed).collect(),
+        };
+        self.put(&request).await
+    }
+
+    /// Sets configuration on several snaps at once, with one request per snap, returning the
+    /// change applying each snap's configuration or why it couldn't be set.
+    ///
+    /// The requests are sent concurrently, as many at a time as the pool has connections for.
+    /// Each is sent from its own task, and fails with [`SnapdRequestError::Cancelled`] if the
+    /// runtime shuts down before it's done.
+    pub async fn set_conf_many(
+        &self,
+        confs: HashMap<SnapName<'static>, Map<String, Value>>,
+    ) -> HashMap<SnapName<'static>, Result<ChangeId, SnapdRequestError>> {
+        let requests = confs.into_iter().map(|(name, conf)| {
+            let client = self.clone();
+            let request = {
+                let name = name.clone();
+                async move { client.set_snap_conf(name, &conf).await }
+            };
+            (name, request)
+        });
+        join_requests(requests).await.into_iter().collect()
+    }
+
+    /// Sets configuration on the snap `name`, and waits for the change applying it to be ready.
+    pub async fn set_snap_conf_and_wait(
+        &self,
+        name: SnapName<'_>,
+        conf: &Map<String, Value>,
+    ) -> Result<Change<'static>, SnapdRequestError> {
+        let id = self.set_snap_conf(name, conf).await?;
+        self.wait_for_change(&id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::decode_result;
+
+    #[test]
+    fn unset_keys_are_absent_and_null_keys_present() {
+        let request = GetSnapConf {
+            name: "hello".into(),
+            keys: vec!["unset".into(), "nothing".into()],
+        };
+        assert_eq!(
+            request.url(Url::parse("http://localhost").unwrap()).query(),
+            Some("keys=unset%2Cnothing")
+        );
+
+        let conf: Map<String, Value> = decode_result(
+            &json!({"type": "sync", "status-code": 200, "status": "OK", "result": {"nothing": null}})
+                .to_string(),
+        );
+        assert!(!conf.contains_key("unset"));
+        assert_eq!(conf.get("nothing"), Some(&Value::Null));
+        assert_eq!(conf_value(&conf, "unset"), None);
+        assert_eq!(conf_value(&conf, "nothing"), Some(&Value::Null));
+    }
+
+    fn conf(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn value_of_a_dotted_key_asked_for_directly() {
+        let conf = conf(json!({"proxy.http": "http://proxy:3128"}));
+
+        assert_eq!(
+            conf_value(&conf, "proxy.http"),
+            Some(&json!("http://proxy:3128"))
+        );
+        assert_eq!(conf_value(&conf, "proxy"), None);
+    }
+
+    #[test]
+    fn value_of_a_dotted_key_under_its_parent() {
+        let conf = conf(json!({
+            "proxy": {"http": "http://proxy:3128", "tls": {"verify": false}},
+        }));
+
+        assert_eq!(
+            conf_value(&conf, "proxy.http"),
+            Some(&json!("http://proxy:3128"))
+        );
+        assert_eq!(conf_value(&conf, "proxy.tls.verify"), Some(&json!(false)));
+        assert_eq!(conf_value(&conf, "proxy.https"), None);
+        assert_eq!(conf_value(&conf, "proxy.http.port"), None);
+    }
+
+    #[cfg(feature = "preserve-order")]
+    #[test]
+    fn keys_keep_snapd_order() {
+        let conf: Map<String, Value> = decode_result(
+            r#"{"type": "sync", "status-code": 200, "status": "OK",
+                "result": {"zoom": 2, "author": "me", "mode": "dark"}}"#,
+        );
+
+        let keys: Vec<_> = conf.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["zoom", "author", "mode"]);
+    }
+
+    #[test]
+    fn unset_keys_are_sent_as_null() {
+        let request = UnsetSnapConf {
+            name: "hello".into(),
+            keys: vec!["theme".into(), "proxy.http".into()],
+        };
+
+        assert_eq!(
+            serde_json::to_string(&request.body()).unwrap(),
+            r#"{"proxy.http":null,"theme":null}"#
+        );
+        assert_eq!(
+            request.url(Url::parse("http://localhost").unwrap()).path(),
+            "/v2/snaps/hello/conf"
+        );
+    }
+}
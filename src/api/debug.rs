@@ -0,0 +1,125 @@
+//! Diagnostics from `snapd`, via `/v2/debug`.
+
+use std::borrow::Cow;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    assertions::{AssertionParseError, BaseDeclarationPolicy},
+    Get,
+};
+
+/// Something `snapd` can report on through `/v2/debug?aspect={name}`.
+pub trait DebugAspect {
+    /// The `aspect` query parameter selecting this aspect.
+    const NAME: &'static str;
+
+    /// The type of the `result` field in `snapd`'s response.
+    type Result: DeserializeOwned;
+}
+
+/// Gets a diagnostic report from `snapd`, via `/v2/debug?aspect={name}`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GetDebug<A> {
+    pub aspect: A,
+}
+
+impl<A: DebugAspect> Get for GetDebug<A> {
+    type Payload = A::Result;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/debug");
+        base_url.query_pairs_mut().append_pair("aspect", A::NAME);
+        base_url
+    }
+}
+
+/// Whether `snapd` can reach the store, for diagnosing network problems.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Connectivity;
+
+impl DebugAspect for Connectivity {
+    const NAME: &'static str = "connectivity";
+    type Result = ConnectivityStatus<'static>;
+}
+
+/// The result of checking whether `snapd` can reach the store.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConnectivityStatus<'a> {
+    /// Whether every store URL could be reached.
+    pub connectivity: bool,
+    /// The store URLs that couldn't be reached.
+    #[serde(default)]
+    pub unreachable: Vec<Cow<'a, str>>,
+}
+
+/// The base declaration, which sets the default policy for connecting interfaces.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BaseDeclaration;
+
+impl DebugAspect for BaseDeclaration {
+    const NAME: &'static str = "base-declaration";
+    type Result = BaseDeclarationText<'static>;
+}
+
+/// The base declaration, as assertion text.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BaseDeclarationText<'a> {
+    pub base_declaration: Cow<'a, str>,
+}
+
+impl BaseDeclarationText<'_> {
+    /// Parses the interface policy out of the base declaration.
+    pub fn policy(&self) -> Result<BaseDeclarationPolicy<'_>, AssertionParseError> {
+        BaseDeclarationPolicy::parse(&self.base_declaration)
+    }
+}
+
+/// Whether something, such as a snap with the `snapd-control` interface, is managing refreshes in
+/// place of `snapd`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CanManageRefreshes;
+
+impl DebugAspect for CanManageRefreshes {
+    const NAME: &'static str = "can-manage-refreshes";
+    type Result = bool;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{assertions::PolicyRule, decode_result};
+
+    #[test]
+    fn aspect_is_sent_as_a_query() {
+        let request = GetDebug {
+            aspect: Connectivity,
+        };
+        assert_eq!(
+            request
+                .url(Url::parse("http://localhost").unwrap())
+                .as_str(),
+            "http://localhost/v2/debug?aspect=connectivity"
+        );
+    }
+
+    #[test]
+    fn connectivity_lists_unreachable_urls() {
+        let status: ConnectivityStatus = decode_result(fixture!("debug_connectivity.json"));
+        assert!(!status.connectivity);
+        assert_eq!(status.unreachable, ["api.snapcraft.io"]);
+    }
+
+    #[test]
+    fn base_declaration_is_parsed() {
+        let text: BaseDeclarationText = decode_result(fixture!("debug_base_declaration.json"));
+        let policy = text.policy().unwrap();
+        assert_eq!(
+            policy.plugs["snapd-control"].deny_auto_connection,
+            Some(PolicyRule::Bool(true))
+        );
+        assert_eq!(policy.slots.len(), 2);
+    }
+}
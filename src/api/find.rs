@@ -0,0 +1,847 @@
+//! Searching the store, via `/v2/find`.
+
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+use hyper::{
+    header::{HeaderValue, ACCEPT_LANGUAGE},
+    HeaderMap,
+};
+use serde::{
+    de::{self, IgnoredAny},
+    Deserialize, Deserializer, Serialize,
+};
+use thiserror::Error;
+use url::Url;
+
+use super::{
+    interfaces::GetInterface,
+    snap::{Channel, Confinement, Publisher, Revision, SnapId, SnapName, SnapStatus, SnapType},
+    snaps::{GetSnap, GetSnaps},
+    Get,
+};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// A snap as described by the store.
+///
+/// Some fields `snapd` sends are deliberately ignored: `developer`, which `publisher` supersedes,
+/// `contact`, `website`, `links`, `channels` and `tracks`, along with `private`, `devmode`,
+/// `jailmode`, `trymode` and `ignore-validation`, which only mean anything for installed snaps.
+/// With the `strict-decode` feature, these and any other unknown fields are rejected.
+///
+/// It serializes to the same form `snapd` sends, so it can be stored and decoded again later:
+///
+/// ```
+/// use snapd::api::find::SnapInfo;
+///
+/// let json = r#"{
+///     "id": "mVyGrEwiqSi5PugCwyH7WgpoQLemtTd6", "name": "hello", "summary": "Hello",
+///     "description": "Says hello", "version": "2.10", "revision": "42",
+///     "channel": "latest/stable", "confinement": "strict", "type": "app", "status": "available"
+/// }"#;
+/// let snap: SnapInfo = serde_json::from_str(json).unwrap();
+///
+/// let stored = serde_json::to_string(&snap).unwrap();
+/// let restored: SnapInfo = serde_json::from_str(&stored).unwrap();
+/// assert!(restored.same_identity(&snap));
+/// assert_eq!(serde_json::to_string(&restored).unwrap(), stored);
+/// ```
+///
+/// Values this crate doesn't know, such as a new [`MediaType`], are decoded as `Unknown` and so
+/// serialize as `unknown`, rather than as what `snapd` sent.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "strict-decode", serde(deny_unknown_fields))]
+pub struct SnapInfo<'a> {
+    pub id: SnapId<'a>,
+    pub name: SnapName<'a>,
+    #[serde(default)]
+    pub title: Option<Cow<'a, str>>,
+    pub summary: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    pub version: Cow<'a, str>,
+    pub revision: Revision,
+    #[serde(default)]
+    pub channel: Option<Channel<'a>>,
+    pub confinement: Confinement,
+    #[serde(rename = "type")]
+    pub snap_type: SnapType,
+    #[serde(default)]
+    pub base: Option<SnapName<'a>>,
+    #[serde(default)]
+    pub publisher: Option<Publisher<'a>>,
+    #[serde(default)]
+    pub license: Option<Cow<'a, str>>,
+    /// Whether the snap is free, or must be bought before it can be installed.
+    pub status: SnapStatus,
+    /// What the snap costs, keyed by currency code such as `USD`. Empty for free snaps.
+    #[serde(default)]
+    pub prices: HashMap<Cow<'a, str>, f64>,
+    #[serde(default)]
+    pub download_size: Option<u64>,
+    #[serde(default)]
+    pub store_url: Option<Cow<'a, str>>,
+    /// The store categories the snap is in, in the order the store lists them.
+    #[serde(default)]
+    pub categories: Vec<StoreCategory<'a>>,
+    /// The snap's icon, screenshots and other images, in the order the store lists them.
+    #[serde(default)]
+    pub media: Vec<Media<'a>>,
+    /// The AppStream IDs of the snap's apps, such as `org.gnome.Calculator`, as
+    /// [`FindSnaps::common_id`] searches for.
+    #[serde(default)]
+    pub common_ids: Vec<Cow<'a, str>>,
+}
+
+impl<'a> SnapInfo<'a> {
+    /// Whether `other` describes the same revision of the same snap, from the same channel,
+    /// whatever its other details.
+    pub fn same_identity(&self, other: &SnapInfo<'_>) -> bool {
+        self.id == other.id
+            && self.name == other.name
+            && self.revision == other.revision
+            && self.channel == other.channel
+    }
+
+    /// Where the snap's icon can be downloaded from, if it has one.
+    pub fn icon_url(&self) -> Option<&str> {
+        self.media
+            .iter()
+            .find(|media| media.media_type == MediaType::Icon)
+            .map(|media| &*media.url)
+    }
+
+    /// The snap's screenshots.
+    pub fn screenshots(&self) -> impl Iterator<Item = &Media<'a>> {
+        self.media
+            .iter()
+            .filter(|media| media.media_type == MediaType::Screenshot)
+    }
+
+    /// The categories the snap is featured in.
+    pub fn featured_categories(&self) -> impl Iterator<Item = &StoreCategory<'a>> {
+        self.categories.iter().filter(|category| category.featured)
+    }
+}
+
+/// An image or video of a snap, hosted by the store.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Media<'a> {
+    #[serde(rename = "type")]
+    pub media_type: MediaType,
+    pub url: Cow<'a, str>,
+    /// The width in pixels, if the store knows it.
+    #[serde(default)]
+    pub width: Option<u32>,
+    /// The height in pixels, if the store knows it.
+    #[serde(default)]
+    pub height: Option<u32>,
+}
+
+/// What a piece of [`Media`] is for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MediaType {
+    Icon,
+    Screenshot,
+    Banner,
+    BannerIcon,
+    Video,
+    #[serde(other)]
+    Unknown,
+}
+
+/// The name of a store category, such as `productivity`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CategoryName<'a>(Cow<'a, str>);
+
+cow_str_newtype!(CategoryName);
+
+/// A store category a snap is in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreCategory<'a> {
+    pub name: CategoryName<'a>,
+    /// Whether the snap is featured in this category.
+    #[serde(default)]
+    pub featured: bool,
+}
+
+/// The snaps returned by a query to `/v2/find`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FindResult<'a> {
+    pub info: Vec<SnapInfo<'a>>,
+}
+
+impl<'a> FindResult<'a> {
+    pub fn len(&self) -> usize {
+        self.info.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.info.is_empty()
+    }
+
+    /// The categories each snap is featured in, in the order the store returned them.
+    pub fn featured_categories(&self) -> impl Iterator<Item = &StoreCategory<'a>> {
+        self.info.iter().flat_map(SnapInfo::featured_categories)
+    }
+}
+
+impl<'a> IntoIterator for FindResult<'a> {
+    type Item = SnapInfo<'a>;
+    type IntoIter = std::vec::IntoIter<SnapInfo<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.info.into_iter()
+    }
+}
+
+impl<'r, 'a> IntoIterator for &'r FindResult<'a> {
+    type Item = &'r SnapInfo<'a>;
+    type IntoIter = std::slice::Iter<'r, SnapInfo<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.info.iter()
+    }
+}
+
+/// Searches the store, via `/v2/find`.
+///
+/// At least one of `query`, `name`, `common_id` or `category` should be set, and `query` can't be
+/// combined with `name` or `common_id`. Any field left unset isn't sent. `snapd` doesn't support
+/// filtering by channel, architecture or price.
+///
+/// ```
+/// use snapd::api::find::FindSnaps;
+///
+/// let request = FindSnaps::builder()
+///     .query("editor")
+///     .category("productivity")
+///     .scope_wide()
+///     .build();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FindSnaps<'a> {
+    /// Free text matched against snaps' names, titles, summaries and descriptions.
+    pub query: Option<Cow<'a, str>>,
+    /// An exact name, or a name prefix if it ends in `*`.
+    pub name: Option<Cow<'a, str>>,
+    /// A common ID from a snap's AppStream metadata, such as `org.gnome.Calculator`.
+    pub common_id: Option<Cow<'a, str>>,
+    pub category: Option<CategoryName<'a>>,
+    /// The name `category` had in older stores, still accepted for compatibility.
+    pub section: Option<Cow<'a, str>>,
+    /// Whether to include snaps not available for this device's architecture or series.
+    pub wide: bool,
+    /// Whether to only return private snaps the logged-in user has access to.
+    pub private: bool,
+    /// The language to localize snaps' titles, summaries and descriptions in, sent as
+    /// `Accept-Language`, such as `fr`. The store falls back to English for snaps that aren't
+    /// translated.
+    pub language: Option<Cow<'a, str>>,
+}
+
+impl<'a> FindSnaps<'a> {
+    pub fn builder() -> FindSnapsBuilder<'a> {
+        FindSnapsBuilder::default()
+    }
+}
+
+impl Get for FindSnaps<'_> {
+    type Payload = FindResult<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/find");
+
+        {
+            let mut query = base_url.query_pairs_mut();
+            let params = [
+                ("q", self.query.as_deref()),
+                ("name", self.name.as_deref()),
+                ("common-id", self.common_id.as_deref()),
+                ("category", self.category.as_ref().map(CategoryName::as_str)),
+                ("section", self.section.as_deref()),
+            ];
+            for (key, value) in params {
+                if let Some(value) = value {
+                    query.append_pair(key, value);
+                }
+            }
+            if self.wide {
+                query.append_pair("scope", "wide");
+            }
+            if self.private {
+                query.append_pair("select", "private");
+            }
+        }
+        if base_url.query() == Some("") {
+            base_url.set_query(None);
+        }
+        base_url
+    }
+
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        // A language that isn't a valid header is left out, leaving the results in English.
+        if let Some(language) = self
+            .language
+            .as_deref()
+            .and_then(|language| HeaderValue::from_str(language).ok())
+        {
+            headers.insert(ACCEPT_LANGUAGE, language);
+        }
+        headers
+    }
+}
+
+/// Builds a [`FindSnaps`] request one filter at a time.
+#[derive(Clone, Debug, Default)]
+pub struct FindSnapsBuilder<'a> {
+    request: FindSnaps<'a>,
+}
+
+impl<'a> FindSnapsBuilder<'a> {
+    pub fn query(mut self, query: impl Into<Cow<'a, str>>) -> Self {
+        self.request.query = Some(query.into());
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<Cow<'a, str>>) -> Self {
+        self.request.name = Some(name.into());
+        self
+    }
+
+    /// Matches snaps whose names start with `prefix`.
+    pub fn name_prefix(mut self, prefix: &str) -> Self {
+        self.request.name = Some(format!("{prefix}*").into());
+        self
+    }
+
+    pub fn common_id(mut self, common_id: impl Into<Cow<'a, str>>) -> Self {
+        self.request.common_id = Some(common_id.into());
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<CategoryName<'a>>) -> Self {
+        self.request.category = Some(category.into());
+        self
+    }
+
+    pub fn section(mut self, section: impl Into<Cow<'a, str>>) -> Self {
+        self.request.section = Some(section.into());
+        self
+    }
+
+    /// Includes snaps not available for this device's architecture or series.
+    pub fn scope_wide(mut self) -> Self {
+        self.request.wide = true;
+        self
+    }
+
+    /// Only returns private snaps the logged-in user has access to.
+    pub fn private(mut self) -> Self {
+        self.request.private = true;
+        self
+    }
+
+    /// Localizes results in `language`, such as `fr`.
+    pub fn language(mut self, language: impl Into<Cow<'a, str>>) -> Self {
+        self.request.language = Some(language.into());
+        self
+    }
+
+    pub fn build(self) -> FindSnaps<'a> {
+        self.request
+    }
+}
+
+/// Lists the installed snaps that have a refresh available, via `/v2/find?select=refresh`.
+///
+/// This doesn't search the store: only local snaps with a pending refresh are returned. The
+/// `version` and `revision` of each result are those of the refresh candidate rather than the
+/// installed snap: [`SnapdClient::refresh_candidates`] pairs them up with the installed ones.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FindRefreshable;
+
+impl Get for FindRefreshable {
+    type Payload = FindResult<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/find");
+        base_url.query_pairs_mut().append_pair("select", "refresh");
+        base_url
+    }
+}
+
+/// Counts the installed snaps that have a refresh available, via `/v2/find?select=refresh`,
+/// without decoding them as [`FindRefreshable`] does.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountRefreshable;
+
+impl Get for CountRefreshable {
+    type Payload = SnapCount;
+
+    fn url(&self, base_url: Url) -> Url {
+        FindRefreshable.url(base_url)
+    }
+}
+
+/// How many snaps `snapd` listed, counted without decoding them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SnapCount(pub usize);
+
+impl<'de> Deserialize<'de> for SnapCount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SnapCountVisitor;
+
+        impl<'de> de::Visitor<'de> for SnapCountVisitor {
+            type Value = SnapCount;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a list of snaps")
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<SnapCount, A::Error> {
+                let mut count = 0;
+                while seq.next_element::<IgnoredAny>()?.is_some() {
+                    count += 1;
+                }
+                Ok(SnapCount(count))
+            }
+        }
+
+        deserializer.deserialize_seq(SnapCountVisitor)
+    }
+}
+
+/// Looks up a snap in the store by its exact name, via `/v2/find?name={name}`.
+///
+/// `snapd` responds with a `snap-not-found` error rather than an empty result if there's no such
+/// snap. [`SnapdClient::find_one`] handles that, and checks exactly one snap came back.
+#[derive(Clone, Debug)]
+pub struct FindSnapByName<'a> {
+    pub name: SnapName<'a>,
+}
+
+impl Get for FindSnapByName<'_> {
+    type Payload = FindResult<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/find");
+        base_url
+            .query_pairs_mut()
+            .append_pair("name", self.name.as_str());
+        base_url
+    }
+}
+
+/// The ways looking up a single snap in the store can fail.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum FindOneError {
+    #[error(transparent)]
+    Request(#[from] SnapdRequestError),
+    #[error("no snap named {0} was found")]
+    NoSnapsFound(SnapName<'static>),
+    /// The store returned more than one snap for a name, which should never happen.
+    #[error("found {count} snaps named {name}, expected one")]
+    Ambiguous {
+        name: SnapName<'static>,
+        count: usize,
+    },
+    #[error("no snap with common ID {0} was found")]
+    NoSnapsWithCommonId(String),
+    /// More than one snap has the common ID, which can happen, as any snap can claim one.
+    #[error("found {count} snaps with common ID {common_id}, expected one")]
+    AmbiguousCommonId { common_id: String, count: usize },
+}
+
+impl SnapdClient {
+    /// Looks up the snap called `name` in the store, failing unless exactly one snap is found.
+    pub async fn find_one(&self, name: SnapName<'_>) -> Result<SnapInfo<'static>, FindOneError> {
+        let result = match self.get(&FindSnapByName { name: name.clone() }).await {
+            Err(SnapdRequestError::Snapd { error, .. })
+                if error.kind.as_deref() == Some("snap-not-found") =>
+            {
+                return Err(FindOneError::NoSnapsFound(name.into_owned()));
+            }
+            result => result?,
+        };
+
+        let mut info = result.info;
+        match info.len() {
+            0 => Err(FindOneError::NoSnapsFound(name.into_owned())),
+            1 => Ok(info.remove(0)),
+            count => Err(FindOneError::Ambiguous {
+                name: name.into_owned(),
+                count,
+            }),
+        }
+    }
+
+    /// Looks up the snaps in the store with the common ID `common_id`, such as
+    /// `org.gnome.Calculator`. Any number may be found, as nothing stops several snaps from
+    /// claiming the same common ID.
+    pub async fn find_by_common_id(
+        &self,
+        common_id: &str,
+    ) -> Result<Vec<SnapInfo<'static>>, SnapdRequestError> {
+        let request = FindSnaps::builder().common_id(common_id).build();
+        match self.get(&request).await {
+            Ok(result) => Ok(result.info),
+            Err(SnapdRequestError::Snapd { error, .. })
+                if error.kind.as_deref() == Some("snap-not-found") =>
+            {
+                Ok(Vec::new())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Looks up the snap in the store with the common ID `common_id`, failing unless exactly one
+    /// snap is found.
+    pub async fn find_one_by_common_id(
+        &self,
+        common_id: &str,
+    ) -> Result<SnapInfo<'static>, FindOneError> {
+        let mut info = self.find_by_common_id(common_id).await?;
+        match info.len() {
+            0 => Err(FindOneError::NoSnapsWithCommonId(common_id.to_owned())),
+            1 => Ok(info.remove(0)),
+            count => Err(FindOneError::AmbiguousCommonId {
+                common_id: common_id.to_owned(),
+                count,
+            }),
+        }
+    }
+
+    /// Whether the store has a snap called `name`, whether or not it's installed.
+    pub async fn exists_in_store(&self, name: SnapName<'_>) -> Result<bool, SnapdRequestError> {
+        match self.get(&FindSnapByName { name }).await {
+            Ok(result) => Ok(!result.info.is_empty()),
+            Err(SnapdRequestError::Snapd { error, .. })
+                if error.kind.as_deref() == Some("snap-not-found") =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Looks up a snap's ID from its name, by searching the store for it.
+///
+/// The reverse of [`SnapNameFromId`](super::assertions::SnapNameFromId).
+#[derive(Clone, Copy, Debug)]
+pub struct SnapIdFromName;
+
+impl SnapIdFromName {
+    pub async fn get_id(
+        name: SnapName<'_>,
+        client: &SnapdClient,
+    ) -> Result<SnapId<'static>, FindOneError> {
+        let cache = client.lookup_cache();
+        if let Some(id) = cache.and_then(|cache| cache.id(&name)) {
+            return Ok(id);
+        }
+
+        let info = client.find_one(name).await?;
+        if let Some(cache) = cache {
+            cache.insert(&info.id, &info.name);
+        }
+        Ok(info.id)
+    }
+}
+
+/// A refresh available for an installed snap.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefreshCandidate<'a> {
+    pub name: SnapName<'a>,
+    pub installed_version: Cow<'a, str>,
+    pub installed_revision: Revision,
+    /// The version the snap would be refreshed to.
+    pub version: Cow<'a, str>,
+    /// The revision the snap would be refreshed to.
+    pub revision: Revision,
+    /// The channel the refresh comes from.
+    pub channel: Option<Channel<'a>>,
+}
+
+impl SnapdClient {
+    /// How many installed snaps have a refresh available, e.g. for a badge on an updates icon.
+    pub async fn refresh_candidates_count(&self) -> Result<usize, SnapdRequestError> {
+        Ok(self.get(&CountRefreshable).await?.0)
+    }
+
+    /// Lists the refreshes available for installed snaps, as `snap refresh --list` does, with the
+    /// version and revision of both the installed snap and its refresh.
+    pub async fn refresh_candidates(
+        &self,
+    ) -> Result<Vec<RefreshCandidate<'static>>, SnapdRequestError> {
+        let candidates = self.get(&FindRefreshable).await?;
+        let mut installed: HashMap<_, _> = self
+            .get(&GetSnaps)
+            .await?
+            .into_iter()
+            .map(|snap| (snap.name.clone(), snap))
+            .collect();
+
+        // A snap removed between the two requests has nothing to be refreshed from.
+        Ok(candidates
+            .into_iter()
+            .filter_map(|candidate| {
+                let current = installed.remove(&candidate.name)?;
+                Some(RefreshCandidate {
+                    name: candidate.name,
+                    installed_version: current.version,
+                    installed_revision: current.revision,
+                    version: candidate.version,
+                    revision: candidate.revision,
+                    channel: candidate.channel,
+                })
+            })
+            .collect())
+    }
+
+    /// Lists the snaps that must be installed along with the snap `name`: its base, and the
+    /// default providers of its content plugs, along with their own dependencies in turn.
+    ///
+    /// The store doesn't describe snaps' plugs, so content providers are only found for snaps
+    /// that are already installed. Apps that don't name a base use `core`. A snap the store can't
+    /// describe, such as one installed from a file, is described by its installed copy instead.
+    /// A dependency that can't be described either way is listed in
+    /// [`unresolved`](Dependencies::unresolved) rather than failing the rest.
+    pub async fn dependencies(&self, name: SnapName<'_>) -> Result<Dependencies, FindOneError> {
+        let name = name.into_owned();
+        let content = self
+            .get(&GetInterface {
+                name: "content".into(),
+            })
+            .await?;
+        let providers = |snap: &SnapName<'_>| {
+            content
+                .iter()
+                .flat_map(|interface| &interface.plugs)
+                .filter(|plug| plug.snap == *snap)
+                .filter_map(|plug| plug.attr("default-provider")?.as_str())
+                // A provider may be written `snap:slot`.
+                .map(|provider| provider.split_once(':').map_or(provider, |(snap, _)| snap))
+                .map(|provider| SnapName::new(provider.to_owned()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut dependencies = Dependencies::default();
+        let mut pending = vec![name.clone()];
+        while let Some(snap) = pending.pop() {
+            let described = match self.find_one(snap.clone()).await {
+                Ok(info) => Ok((info.base, info.snap_type)),
+                Err(e) => self
+                    .get(&GetSnap { name: snap.clone() })
+                    .await
+                    .map(|installed| (installed.base, installed.snap_type))
+                    .map_err(|_| e),
+            };
+            let base = match described {
+                Ok((Some(base), _)) if base.as_str() != "none" => Some(base),
+                Ok((None, SnapType::App)) => Some(SnapName::new("core")),
+                Ok(_) => None,
+                Err(e) if snap == name => return Err(e),
+                Err(e) => {
+                    dependencies.unresolved.push((snap.clone(), e));
+                    None
+                }
+            };
+
+            for dependency in base.into_iter().chain(providers(&snap)) {
+                if dependency != name && !dependencies.snaps.contains(&dependency) {
+                    dependencies.snaps.push(dependency.clone());
+                    pending.push(dependency);
+                }
+            }
+        }
+        Ok(dependencies)
+    }
+}
+
+/// The snaps that must be installed along with a snap, as found by
+/// [`SnapdClient::dependencies`].
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct Dependencies {
+    pub snaps: Vec<SnapName<'static>>,
+    /// The dependencies that neither the store nor the installed snaps could describe, with why
+    /// the store couldn't. Their own dependencies are missing from `snaps`.
+    pub unresolved: Vec<(SnapName<'static>, FindOneError)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decode_result;
+
+    fn base_url() -> Url {
+        Url::parse("http://localhost").unwrap()
+    }
+
+    #[test]
+    fn find_refreshable_lists_refresh_candidates() {
+        assert_eq!(
+            FindRefreshable.url(base_url()).as_str(),
+            "http://localhost/v2/find?select=refresh"
+        );
+
+        let refreshable: FindResult = decode_result(fixture!("find_refreshable.json"));
+        assert_eq!(refreshable.len(), 1);
+        let snap = &refreshable.info[0];
+        assert_eq!(snap.name.as_str(), "hello-world");
+        assert_eq!(snap.version, "6.4");
+        assert_eq!(snap.revision, Revision::new(29));
+        assert_eq!(snap.channel.as_ref().unwrap().to_string(), "latest/stable");
+    }
+
+    #[test]
+    fn featured_categories_keep_the_stores_order() {
+        let found: FindResult = decode_result(fixture!("find_categories.json"));
+
+        let featured: Vec<_> = found
+            .featured_categories()
+            .map(|category| category.name.as_str())
+            .collect();
+        assert_eq!(featured, ["productivity", "featured", "music-and-audio"]);
+
+        let mut names: Vec<_> = found.info[0]
+            .categories
+            .iter()
+            .map(|category| category.name.clone())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                CategoryName::new("featured"),
+                CategoryName::new("office"),
+                CategoryName::new("productivity")
+            ]
+        );
+    }
+
+    #[test]
+    fn find_result_iterates_its_snaps() {
+        let found: FindResult = decode_result(fixture!("find_categories.json"));
+        assert_eq!(found.len(), 2);
+        assert!(!found.is_empty());
+
+        let borrowed: Vec<_> = (&found)
+            .into_iter()
+            .map(|snap| snap.name.as_str())
+            .collect();
+        assert_eq!(borrowed, ["libreoffice", "spotify"]);
+
+        let mut names = Vec::new();
+        for snap in found {
+            names.push(snap.name);
+        }
+        assert_eq!(
+            names,
+            [SnapName::new("libreoffice"), SnapName::new("spotify")]
+        );
+    }
+
+    #[cfg(not(feature = "strict-decode"))]
+    #[test]
+    fn unmodelled_fields_are_ignored() {
+        let found: FindResult = decode_result(fixture!("find_unmodelled_fields.json"));
+        assert_eq!(found.info[0].name.as_str(), "hello-world");
+    }
+
+    #[cfg(feature = "strict-decode")]
+    #[test]
+    fn strict_decode_rejects_unmodelled_fields() {
+        let error = serde_json::from_str::<crate::api::SyncResponse<FindResult>>(fixture!(
+            "find_unmodelled_fields.json"
+        ))
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field"), "{error}");
+    }
+
+    #[test]
+    fn builder_assembles_the_query() {
+        let query = |request: FindSnaps| request.url(base_url()).query().map(str::to_owned);
+
+        assert_eq!(
+            query(
+                FindSnaps::builder()
+                    .query("text editor")
+                    .category("productivity")
+                    .scope_wide()
+                    .build()
+            )
+            .as_deref(),
+            Some("q=text+editor&category=productivity&scope=wide")
+        );
+        assert_eq!(
+            query(FindSnaps::builder().name_prefix("hello").private().build()).as_deref(),
+            Some("name=hello*&select=private")
+        );
+        assert_eq!(
+            query(
+                FindSnaps::builder()
+                    .common_id("org.gnome.Calculator")
+                    .build()
+            )
+            .as_deref(),
+            Some("common-id=org.gnome.Calculator")
+        );
+        assert_eq!(query(FindSnaps::builder().build()), None);
+    }
+
+    #[test]
+    fn paid_snaps_carry_their_prices() {
+        let snaps: Vec<SnapInfo<'static>> = decode_result(fixture!("find_priced.json"));
+
+        let snap = &snaps[0];
+        assert_eq!(snap.status, SnapStatus::Priced);
+        assert_eq!(snap.prices["USD"], 99.0);
+        assert_eq!(snap.prices["EUR"], 89.5);
+    }
+
+    #[test]
+    fn icon_url_is_picked_out_of_mixed_media() {
+        let snaps: Vec<SnapInfo<'static>> = decode_result(fixture!("find_media.json"));
+
+        let snap = &snaps[0];
+        assert_eq!(
+            snap.icon_url(),
+            Some(
+                "https://dashboard.snapcraft.io/site_media/appmedia/2017/12/spotify-linux-256.png"
+            )
+        );
+        let screenshots: Vec<_> = snap.screenshots().map(|media| &*media.url).collect();
+        assert_eq!(
+            screenshots,
+            [
+                "https://dashboard.snapcraft.io/site_media/appmedia/2017/12/spotify-1.png",
+                "https://dashboard.snapcraft.io/site_media/appmedia/2017/12/spotify-2.png",
+            ]
+        );
+        assert_eq!(snap.media[0].width, Some(1280));
+        assert_eq!(snap.media[2].height, None);
+    }
+
+    #[test]
+    fn snap_info_round_trips() {
+        let found: FindResult = decode_result(fixture!("find_media.json"));
+        let mut snap = found.info[0].clone();
+        snap.summary = Cow::Borrowed("A borrowed summary");
+
+        let serialized = serde_json::to_value(&snap).unwrap();
+        assert_eq!(serialized["summary"], "A borrowed summary");
+        let decoded: SnapInfo = serde_json::from_value(serialized.clone()).unwrap();
+
+        assert!(decoded.same_identity(&snap));
+        assert_eq!(decoded.summary, "A borrowed summary");
+        assert_eq!(decoded.media.len(), snap.media.len());
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serialized);
+    }
+}
@@ -0,0 +1,164 @@
+//! Interfaces, and the plugs and slots snaps connect through them, via `/v2/interfaces`.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use url::Url;
+
+use super::{snap::SnapName, Get};
+
+/// An interface, along with the plugs and slots of it that snaps have.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Interface<'a> {
+    pub name: Cow<'a, str>,
+    /// A one-line description of what the interface allows, sent when documentation is asked for.
+    #[serde(default)]
+    pub summary: Option<Cow<'a, str>>,
+    /// Where the interface's full documentation is, sent when documentation is asked for.
+    #[serde(default)]
+    pub doc_url: Option<Cow<'a, str>>,
+    #[serde(default)]
+    pub plugs: Vec<Plug<'a>>,
+    #[serde(default)]
+    pub slots: Vec<Slot<'a>>,
+}
+
+/// A snap's plug, through which it consumes what an interface provides.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Plug<'a> {
+    pub snap: SnapName<'a>,
+    #[serde(rename = "plug")]
+    pub name: Cow<'a, str>,
+    #[serde(default)]
+    pub label: Option<Cow<'a, str>>,
+    /// The plug's attributes, whose names and meanings depend on the interface. They're sorted by
+    /// name, unless the `preserve-order` feature keeps them in the order `snapd` sent them.
+    #[serde(default)]
+    pub attrs: Map<String, Value>,
+}
+
+impl Plug<'_> {
+    /// The attribute `key`, such as the `target` of a `content` plug.
+    pub fn attr(&self, key: &str) -> Option<&Value> {
+        self.attrs.get(key)
+    }
+}
+
+/// A snap's slot, through which it provides what an interface offers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Slot<'a> {
+    pub snap: SnapName<'a>,
+    #[serde(rename = "slot")]
+    pub name: Cow<'a, str>,
+    #[serde(default)]
+    pub label: Option<Cow<'a, str>>,
+    /// The slot's attributes, whose names and meanings depend on the interface. Like a plug's,
+    /// they're sorted by name without the `preserve-order` feature.
+    #[serde(default)]
+    pub attrs: Map<String, Value>,
+}
+
+impl Slot<'_> {
+    /// The attribute `key`, such as the `source` of a `content` slot.
+    pub fn attr(&self, key: &str) -> Option<&Value> {
+        self.attrs.get(key)
+    }
+}
+
+/// Gets a single interface with its documentation, plugs and slots, via
+/// `/v2/interfaces?select=all&names={name}&doc=true&plugs=true&slots=true`.
+///
+/// `snapd` responds with a list, which is empty if there's no such interface.
+#[derive(Clone, Debug)]
+pub struct GetInterface<'a> {
+    pub name: Cow<'a, str>,
+}
+
+impl Get for GetInterface<'_> {
+    type Payload = Vec<Interface<'static>>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/interfaces");
+        base_url
+            .query_pairs_mut()
+            .append_pair("select", "all")
+            .append_pair("names", &self.name)
+            .append_pair("doc", "true")
+            .append_pair("plugs", "true")
+            .append_pair("slots", "true");
+        base_url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decode_result;
+
+    #[test]
+    fn interface_documentation_is_decoded() {
+        let request = GetInterface {
+            name: "home".into(),
+        };
+        assert_eq!(
+            request.url(Url::parse("http://localhost").unwrap()).query(),
+            Some("select=all&names=home&doc=true&plugs=true&slots=true")
+        );
+
+        let interfaces: Vec<Interface> = decode_result(fixture!("interface_home.json"));
+        let home = &interfaces[0];
+        assert_eq!(home.name, "home");
+        assert_eq!(
+            home.summary.as_deref(),
+            Some("allows access to non-hidden files in the home directory")
+        );
+        assert_eq!(
+            home.doc_url.as_deref(),
+            Some("https://snapcraft.io/docs/home-interface")
+        );
+        assert_eq!(home.plugs[0].snap.as_str(), "firefox");
+        assert_eq!(home.slots[0].snap.as_str(), "snapd");
+    }
+
+    #[test]
+    fn content_attributes_are_read() {
+        let interfaces: Vec<Interface> = decode_result(fixture!("interface_content.json"));
+        let content = &interfaces[0];
+
+        let slot = &content.slots[0];
+        assert_eq!(
+            slot.attr("source"),
+            Some(&serde_json::json!({"read": ["$SNAP/share/themes"]}))
+        );
+        assert_eq!(slot.attr("content").and_then(Value::as_str), Some("themes"));
+
+        let plug = &content.plugs[0];
+        assert_eq!(
+            plug.attr("target").and_then(Value::as_str),
+            Some("$SNAP/data-dir/themes")
+        );
+        assert_eq!(plug.attr("missing"), None);
+    }
+
+    #[cfg(feature = "preserve-order")]
+    #[test]
+    fn attributes_keep_snapd_order() {
+        let plug: Plug = serde_json::from_str(
+            r#"{
+                "snap": "firefox",
+                "plug": "gtk-3-themes",
+                "attrs": {
+                    "target": "$SNAP/data-dir/themes",
+                    "default-provider": "gtk-common-themes",
+                    "content": "gtk-3-themes"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let names: Vec<_> = plug.attrs.keys().map(String::as_str).collect();
+        assert_eq!(names, ["target", "default-provider", "content"]);
+    }
+}
@@ -0,0 +1,48 @@
+//! The device's model, via `/v2/model`.
+
+use std::borrow::Cow;
+
+use serde::Serialize;
+use url::Url;
+
+use super::{changes::ChangeId, AsyncResponse, Post};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// Moves an Ubuntu Core device to a new model, via a POST to `/v2/model`.
+///
+/// `snapd` takes the assertion as text within a JSON body, rather than as a body of type
+/// `application/x.ubuntu.assertion`. Once the device is remodelled, `snapd` registers it for a new
+/// serial itself, so no request is needed for that.
+#[derive(Clone, Debug, Serialize)]
+pub struct SetModel<'a> {
+    /// The new model assertion, signed by the device's brand.
+    #[serde(rename = "new-model")]
+    pub model_assertion: Cow<'a, str>,
+}
+
+impl Post for SetModel<'_> {
+    type Body<'b>
+        = &'b Self
+    where
+        Self: 'b;
+    type Response = AsyncResponse;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/model");
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self
+    }
+}
+
+impl SnapdClient {
+    /// Remodels the device to the model in `model_assertion`, returning the change doing so.
+    pub async fn set_model(&self, model_assertion: &str) -> Result<ChangeId, SnapdRequestError> {
+        self.post(&SetModel {
+            model_assertion: Cow::Borrowed(model_assertion),
+        })
+        .await
+    }
+}
@@ -0,0 +1,228 @@
+//! Events `snapd` records for clients to wait on, via `/v2/notices`.
+//!
+//! Notices were added in `snapd` 2.59. Older versions respond to `/v2/notices` with a `404`.
+
+use std::{borrow::Cow, collections::HashMap, time::Duration};
+
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    changes::{Change, ChangeId, GetChange},
+    system_info::SnapdVersion,
+    time::{SnapdDuration, Timestamp},
+    Get, Paginated,
+};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// The first version of `snapd` with notices.
+const NOTICES_VERSION: SnapdVersion = SnapdVersion::new(2, 59, 0);
+
+/// How long [`SnapdClient::wait_for_change_via_notices`] asks `snapd` to hold each request for
+/// notices open.
+const NOTICE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Something that happened, which `snapd` records until it expires.
+///
+/// A notice is identified by its type and key. When the same thing happens again, the existing
+/// notice is updated rather than a new one being recorded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Notice<'a> {
+    pub id: Cow<'a, str>,
+    /// The user the notice is visible to, or `None` if it's visible to every user.
+    #[serde(default)]
+    pub user_id: Option<u32>,
+    #[serde(rename = "type")]
+    pub notice_type: NoticeType,
+    /// What the notice is about, such as the ID of the change for a
+    /// [`ChangeUpdate`](NoticeType::ChangeUpdate).
+    pub key: Cow<'a, str>,
+    pub first_occurred: Timestamp,
+    pub last_occurred: Timestamp,
+    /// When the notice last occurred in a way that clients should be told about again.
+    pub last_repeated: Timestamp,
+    pub occurrences: u64,
+    /// Details from the last occurrence, which depend on the notice's type.
+    #[serde(default)]
+    pub last_data: HashMap<Cow<'a, str>, Cow<'a, str>>,
+    /// How long after it was last repeated the notice must occur again to be repeated, rather than
+    /// just updated. Without one, every occurrence repeats it.
+    #[serde(default)]
+    pub repeat_after: Option<SnapdDuration>,
+    /// How long after it last occurred the notice is forgotten.
+    #[serde(default)]
+    pub expire_after: Option<SnapdDuration>,
+}
+
+/// The kinds of [`Notice`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NoticeType {
+    /// A change's status changed. The key is the change's ID.
+    ChangeUpdate,
+    /// A warning was added. The key is the warning's message.
+    Warning,
+    /// A snap's refresh was held back because it's running. The key is `-`.
+    RefreshInhibit,
+    /// A snap couldn't be run because it's being refreshed. The key is the snap's name.
+    SnapRunInhibit,
+    /// A type of notice this crate doesn't know about.
+    #[serde(other)]
+    Unknown,
+}
+
+impl NoticeType {
+    /// The name `snapd` knows this type by, or `None` for [`Unknown`](Self::Unknown).
+    pub fn as_str(self) -> Option<&'static str> {
+        match self {
+            Self::ChangeUpdate => Some("change-update"),
+            Self::Warning => Some("warning"),
+            Self::RefreshInhibit => Some("refresh-inhibit"),
+            Self::SnapRunInhibit => Some("snap-run-inhibit"),
+            Self::Unknown => None,
+        }
+    }
+}
+
+/// Lists notices, via `/v2/notices`.
+///
+/// Empty filters match everything.
+#[derive(Clone, Debug, Default)]
+pub struct GetNotices<'a> {
+    pub types: Vec<NoticeType>,
+    pub keys: Vec<Cow<'a, str>>,
+    /// Only includes notices last repeated after this time.
+    pub after: Option<Timestamp>,
+    /// If no notices match yet, how long `snapd` should hold the request open waiting for one,
+    /// rather than responding with none straight away.
+    ///
+    /// The client puts no time limit on requests itself, and its
+    /// [connect timeout](crate::SnapdConnectionManager::with_connect_timeout) only covers
+    /// connecting, so nothing cuts the wait short.
+    pub timeout: Option<Duration>,
+}
+
+impl Get for GetNotices<'_> {
+    type Payload = Vec<Notice<'static>>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/notices");
+        {
+            let mut query = base_url.query_pairs_mut();
+            let types = self
+                .types
+                .iter()
+                .filter_map(|notice_type| notice_type.as_str())
+                .collect::<Vec<_>>();
+            if !types.is_empty() {
+                query.append_pair("types", &types.join(","));
+            }
+            if !self.keys.is_empty() {
+                query.append_pair("keys", &self.keys.join(","));
+            }
+            if let Some(after) = self.after {
+                query.append_pair("after", &after.to_string());
+            }
+            if let Some(timeout) = self.timeout {
+                query.append_pair("timeout", &SnapdDuration(timeout).to_string());
+            }
+        }
+        if base_url.query() == Some("") {
+            base_url.set_query(None);
+        }
+        base_url
+    }
+}
+
+/// Each page holds the notices repeated since the last, until there are none. With a
+/// [`timeout`](GetNotices::timeout), each request waits for new notices before ending them, so the
+/// pages follow notices as they happen.
+impl Paginated for GetNotices<'_> {
+    fn advance(&mut self, page: &Vec<Notice<'static>>) -> bool {
+        match page.iter().map(|notice| notice.last_repeated).max() {
+            Some(last) => {
+                self.after = Some(last);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl SnapdClient {
+    /// Waits for the change `id` to be ready, returning its final state, like
+    /// [`wait_for_change`](Self::wait_for_change).
+    ///
+    /// Rather than polling the change, this waits for `snapd` to report that it changed with a
+    /// [`ChangeUpdate`](NoticeType::ChangeUpdate) notice. On versions of `snapd` too old to have
+    /// notices, it falls back to polling.
+    pub async fn wait_for_change_via_notices(
+        &self,
+        id: &ChangeId,
+    ) -> Result<Change<'static>, SnapdRequestError> {
+        if !self.snapd_is_at_least(NOTICES_VERSION).await? {
+            return self.wait_for_change(id).await;
+        }
+
+        let mut notices = GetNotices {
+            types: vec![NoticeType::ChangeUpdate],
+            keys: vec![Cow::Borrowed(id.as_str())],
+            after: None,
+            timeout: Some(NOTICE_TIMEOUT),
+        };
+
+        self.cancellable(async {
+            loop {
+                let change = self.get(&GetChange { id }).await?;
+                if change.ready {
+                    return Ok(change);
+                }
+
+                // Any update after the change was fetched is newer than the notices seen so far, so
+                // it can't be missed.
+                match self.get(&notices).await {
+                    Ok(seen) => {
+                        notices.advance(&seen);
+                    }
+                    // A development build of an older `snapd` may not have been caught by the version
+                    // check.
+                    Err(SnapdRequestError::Snapd { status, .. })
+                        if status == StatusCode::NOT_FOUND =>
+                    {
+                        return self.wait_for_change(id).await;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_is_sent_as_a_snapd_duration() {
+        let notices = GetNotices {
+            types: vec![NoticeType::ChangeUpdate, NoticeType::Unknown],
+            timeout: Some(Duration::from_secs(30)),
+            ..GetNotices::default()
+        };
+        assert_eq!(
+            notices
+                .url(Url::parse("http://localhost").unwrap())
+                .as_str(),
+            "http://localhost/v2/notices?types=change-update&timeout=30s"
+        );
+        assert_eq!(
+            GetNotices::default()
+                .url(Url::parse("http://localhost").unwrap())
+                .as_str(),
+            "http://localhost/v2/notices"
+        );
+    }
+}
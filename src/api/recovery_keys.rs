@@ -0,0 +1,96 @@
+//! Full-disk-encryption recovery keys, via `/v2/system-recovery-keys`.
+
+use std::borrow::Cow;
+
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use url::Url;
+
+use super::{Get, Post, SyncResponse};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// The keys that unlock an encrypted Ubuntu Core device when its TPM can't.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RecoveryKeys<'a> {
+    /// Unlocks the data and save partitions, for use at boot.
+    pub recovery_key: Cow<'a, str>,
+    /// Unlocks the save partition only, for use when reinstalling. Newer devices don't have one.
+    #[serde(default)]
+    pub reinstall_key: Option<Cow<'a, str>>,
+}
+
+/// Gets the device's recovery keys, via `/v2/system-recovery-keys`.
+///
+/// This needs root, and only works on devices using full-disk encryption.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GetRecoveryKeys;
+
+impl Get for GetRecoveryKeys {
+    type Payload = RecoveryKeys<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/system-recovery-keys");
+        base_url
+    }
+}
+
+/// Removes the device's recovery keys, via a POST to `/v2/system-recovery-keys`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RemoveRecoveryKeys;
+
+/// The body of a [`RemoveRecoveryKeys`].
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum RecoveryKeysAction {
+    Remove,
+}
+
+impl Post for RemoveRecoveryKeys {
+    type Body<'a> = RecoveryKeysAction;
+    type Response = SyncResponse<IgnoredAny>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/system-recovery-keys");
+        base_url
+    }
+
+    fn body(&self) -> RecoveryKeysAction {
+        RecoveryKeysAction::Remove
+    }
+}
+
+impl SnapdClient {
+    /// Removes the device's recovery keys. Unlike most POSTs, this finishes before `snapd`
+    /// responds rather than starting a change.
+    pub async fn remove_recovery_keys(&self) -> Result<(), SnapdRequestError> {
+        self.post(&RemoveRecoveryKeys).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decode_result;
+
+    #[test]
+    fn recovery_keys_are_decoded() {
+        let keys: RecoveryKeys = decode_result(fixture!("system_recovery_keys.json"));
+        assert_eq!(
+            keys.recovery_key,
+            "61665-00531-54469-09783-47273-19035-40077-28287"
+        );
+        assert_eq!(
+            keys.reinstall_key.as_deref(),
+            Some("28784-62412-27478-25662-31240-01732-52416-31458")
+        );
+    }
+
+    #[test]
+    fn remove_body_is_an_action() {
+        assert_eq!(
+            serde_json::to_value(RemoveRecoveryKeys.body()).unwrap(),
+            serde_json::json!({"action": "remove"})
+        );
+    }
+}
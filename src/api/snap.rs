@@ -0,0 +1,385 @@
+//! Types describing snaps, shared between the store and installed-snap endpoints.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// The name of a snap, e.g. `firefox`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SnapName<'a>(Cow<'a, str>);
+
+cow_str_newtype!(SnapName);
+
+/// The store's unique identifier for a snap, which unlike its name never changes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SnapId<'a>(Cow<'a, str>);
+
+cow_str_newtype!(SnapId);
+
+/// The name of an app within a snap.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct App<'a>(Cow<'a, str>);
+
+cow_str_newtype!(App);
+
+/// A command exposed by a snap, written `snap.app`.
+///
+/// The app sharing the snap's name is written as just `snap`, and has no [`app`](Self::app).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SnapCommand<'a, 'b> {
+    pub snap: SnapName<'a>,
+    pub app: Option<App<'b>>,
+}
+
+impl<'a, 'b> SnapCommand<'a, 'b> {
+    pub fn new(snap: SnapName<'a>, app: App<'b>) -> Self {
+        Self {
+            snap,
+            app: Some(app),
+        }
+    }
+
+    /// The command for the app sharing `snap`'s name.
+    pub fn name_only(snap: SnapName<'a>) -> Self {
+        Self { snap, app: None }
+    }
+
+    /// Splits a `snap.app` or `snap` command, borrowing from `raw`.
+    pub fn parse(raw: &'a str) -> SnapCommand<'a, 'a> {
+        match raw.split_once('.') {
+            Some((snap, app)) => SnapCommand::new(snap.into(), app.into()),
+            None => SnapCommand::name_only(raw.into()),
+        }
+    }
+
+    /// Splits a `snap.app` or `snap` command, taking ownership of `raw`.
+    ///
+    /// The snap's name keeps `raw`'s buffer, so only the app's name is copied out of it.
+    pub fn parse_owned(mut raw: String) -> SnapCommand<'static, 'static> {
+        match raw.find('.') {
+            Some(dot) => {
+                let app = App::new(raw[dot + 1..].to_owned());
+                raw.truncate(dot);
+                SnapCommand::new(SnapName::new(raw), app)
+            }
+            None => SnapCommand::name_only(SnapName::new(raw)),
+        }
+    }
+
+    pub fn into_owned(self) -> SnapCommand<'static, 'static> {
+        SnapCommand {
+            snap: self.snap.into_owned(),
+            app: self.app.map(App::into_owned),
+        }
+    }
+}
+
+impl<'a> From<SnapName<'a>> for SnapCommand<'a, '_> {
+    fn from(snap: SnapName<'a>) -> Self {
+        Self::name_only(snap)
+    }
+}
+
+impl<'a, 'b> From<(SnapName<'a>, App<'b>)> for SnapCommand<'a, 'b> {
+    fn from((snap, app): (SnapName<'a>, App<'b>)) -> Self {
+        Self::new(snap, app)
+    }
+}
+
+impl fmt::Display for SnapCommand<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.app {
+            Some(app) => write!(f, "{}.{app}", self.snap),
+            None => write!(f, "{}", self.snap),
+        }
+    }
+}
+
+impl Serialize for SnapCommand<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Borrows from the input where it can, such as when decoding from a `&str`, so a command that
+/// needs no unescaping is decoded without allocating.
+impl<'de: 'a, 'a> Deserialize<'de> for SnapCommand<'a, 'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SnapCommandVisitor;
+
+        impl<'de> de::Visitor<'de> for SnapCommandVisitor {
+            type Value = SnapCommand<'de, 'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a snap command")
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+                Ok(SnapCommand::parse(v))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(SnapCommand::parse_owned(v.to_owned()))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+                Ok(SnapCommand::parse_owned(v))
+            }
+        }
+
+        deserializer.deserialize_str(SnapCommandVisitor)
+    }
+}
+
+/// A channel a snap is published to, e.g. `latest/stable`.
+///
+/// Channels are compared as `snapd` reads them, with the `latest` track and `stable` risk filled
+/// in where they're left out, so `stable`, `latest` and `latest/stable` are all equal.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Channel<'a>(Cow<'a, str>);
+
+cow_str_newtype!(Channel);
+
+impl Channel<'_> {
+    /// The risk levels a channel can have, from most to least stable.
+    const RISKS: [&'static str; 4] = ["stable", "candidate", "beta", "edge"];
+
+    /// Splits the channel into its track, risk and branch, filling in the defaults.
+    ///
+    /// A channel of more than three parts isn't valid, and is returned whole as its track.
+    fn parts(&self) -> (&str, &str, Option<&str>) {
+        let is_risk = |part: &str| Self::RISKS.contains(&part);
+        let mut parts = self.0.split('/');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(risk), None, _, _) if is_risk(risk) => ("latest", risk, None),
+            (Some(track), None, _, _) => (track, "stable", None),
+            (Some(risk), Some(branch), None, _) if is_risk(risk) => ("latest", risk, Some(branch)),
+            (Some(track), Some(risk), None, _) => (track, risk, None),
+            (Some(track), Some(risk), Some(branch), None) => (track, risk, Some(branch)),
+            _ => (&self.0, "", None),
+        }
+    }
+}
+
+impl PartialEq for Channel<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for Channel<'_> {}
+
+impl Hash for Channel<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts().hash(state);
+    }
+}
+
+/// A revision of a snap.
+///
+/// Revisions from the store are positive. Snaps installed from a local file without an assertion
+/// get negative revisions, which `snapd` displays as `x1`, `x2`, and so on. The zero revision is
+/// displayed as `unset`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Revision(i64);
+
+impl Revision {
+    pub const UNSET: Self = Self(0);
+
+    pub const fn new(revision: i64) -> Self {
+        Self(revision)
+    }
+
+    pub const fn get(self) -> i64 {
+        self.0
+    }
+
+    /// Whether this revision was installed locally rather than from the store.
+    pub const fn is_local(self) -> bool {
+        self.0 < 0
+    }
+}
+
+impl fmt::Display for Revision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => f.write_str("unset"),
+            n if n < 0 => write!(f, "x{}", -n),
+            n => write!(f, "{n}"),
+        }
+    }
+}
+
+/// The error returned when a string isn't a valid [`Revision`].
+#[derive(Clone, Debug, Error)]
+#[error("invalid snap revision {0:?}")]
+pub struct ParseRevisionError(String);
+
+impl FromStr for Revision {
+    type Err = ParseRevisionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "unset" {
+            return Ok(Self::UNSET);
+        }
+
+        let (digits, sign) = match s.strip_prefix('x') {
+            Some(digits) => (digits, -1),
+            None => (s, 1),
+        };
+
+        match digits.parse::<i64>() {
+            Ok(n) if n > 0 && digits.bytes().all(|b| b.is_ascii_digit()) => Ok(Self(n * sign)),
+            _ => Err(ParseRevisionError(s.to_owned())),
+        }
+    }
+}
+
+impl Serialize for Revision {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Revision {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RevisionVisitor;
+
+        impl de::Visitor<'_> for RevisionVisitor {
+            type Value = Revision;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a snap revision")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Revision, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(RevisionVisitor)
+    }
+}
+
+/// How strictly a snap is sandboxed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confinement {
+    Strict,
+    Classic,
+    Devmode,
+}
+
+/// The role a snap plays on the system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapType {
+    App,
+    Base,
+    Gadget,
+    Kernel,
+    Os,
+    Snapd,
+}
+
+/// The account that publishes a snap.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Publisher<'a> {
+    pub id: Cow<'a, str>,
+    pub username: Cow<'a, str>,
+    pub display_name: Cow<'a, str>,
+    #[serde(default)]
+    pub validation: Option<PublisherValidation>,
+}
+
+/// How far the store vouches for a publisher's identity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PublisherValidation {
+    Verified,
+    Starred,
+    Unproven,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Whether a snap is available, installed, or running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapStatus {
+    /// In the store, but not installed.
+    Available,
+    /// In the store, but must be bought before it can be installed.
+    Priced,
+    /// Installed, but disabled.
+    Installed,
+    /// Installed and enabled.
+    Active,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{hash_map::RandomState, HashSet},
+        hash::BuildHasher,
+    };
+
+    use super::*;
+
+    #[test]
+    fn commands_from_names() {
+        let command = SnapCommand::from(SnapName::from("hello"));
+        assert_eq!(command, SnapCommand::name_only("hello".into()));
+        assert_eq!(command.to_string(), "hello");
+
+        let command = SnapCommand::from((SnapName::from("lxd"), App::from("lxc")));
+        assert_eq!(command, SnapCommand::new("lxd".into(), "lxc".into()));
+        assert_eq!(command.to_string(), "lxd.lxc");
+    }
+
+    #[test]
+    fn equivalent_channels_are_equal_and_hash_equal() {
+        let spellings = ["stable", "latest", "latest/stable"].map(Channel::new);
+        for channel in &spellings {
+            assert_eq!(*channel, spellings[0]);
+        }
+
+        let state = RandomState::new();
+        assert!(spellings
+            .iter()
+            .all(|channel| state.hash_one(channel) == state.hash_one(&spellings[0])));
+
+        assert_eq!(
+            Channel::new("edge/fix-123"),
+            Channel::new("latest/edge/fix-123")
+        );
+        assert_ne!(Channel::new("edge"), Channel::new("stable"));
+        assert_ne!(Channel::new("5.21"), Channel::new("latest"));
+
+        let tracking: HashSet<_> = spellings.into_iter().collect();
+        assert_eq!(tracking.len(), 1);
+    }
+
+    #[test]
+    fn revisions_can_be_map_keys() {
+        let revisions: HashSet<Revision> = ["42", "x1", "42", "unset"]
+            .into_iter()
+            .map(|revision| revision.parse().unwrap())
+            .collect();
+
+        assert_eq!(revisions.len(), 3);
+        assert!(revisions.contains(&Revision::new(-1)));
+        assert!(revisions.contains(&Revision::UNSET));
+    }
+}
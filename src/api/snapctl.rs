@@ -0,0 +1,139 @@
+//! Running `snapctl` commands from a snap's hooks and apps, via `/v2/snapctl`.
+//!
+//! Inside a snap, `snapd` is reached through [`SNAPD_SNAP_SOCKET`](crate::SNAPD_SNAP_SOCKET):
+//!
+//! ```no_run
+//! use snapd::{SnapdClient, SnapdConnectionManager, SnapdPool, SNAPD_SNAP_SOCKET};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let pool = SnapdPool::builder(SnapdConnectionManager::new(SNAPD_SNAP_SOCKET)).build()?;
+//! let client = SnapdClient::from_pool(pool);
+//!
+//! let context = std::env::var("SNAP_COOKIE")?;
+//! let output = client.snapctl(&context, &["get", "proxy.http"]).await?;
+//! print!("{}", output.stdout);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{Envelope, Post};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// Runs a `snapctl` command, via a POST to `/v2/snapctl`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SnapCtl<'a> {
+    /// Identifies the snap, and the hook if one is running, to `snapd`. Snaps are given it in
+    /// the `SNAP_COOKIE` environment variable.
+    #[serde(rename = "context-id")]
+    pub context_id: Cow<'a, str>,
+    /// The command's arguments, such as `["get", "proxy.http"]`.
+    pub args: Vec<Cow<'a, str>>,
+}
+
+impl Post for SnapCtl<'_> {
+    type Body<'b>
+        = &'b Self
+    where
+        Self: 'b;
+    type Response = SnapCtlResponse;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/snapctl");
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self
+    }
+}
+
+/// What a `snapctl` command printed, and how it exited.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SnapCtlOutput<'a> {
+    #[serde(default)]
+    pub stdout: Cow<'a, str>,
+    #[serde(default)]
+    pub stderr: Cow<'a, str>,
+    #[serde(default)]
+    pub exit_code: i32,
+}
+
+impl SnapCtlOutput<'_> {
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// The envelope of a response to [`SnapCtl`].
+///
+/// A command that ran but failed is sent as an error, with its output in the error's
+/// [`value`](super::SnapdError::value), yet with a `200 OK`. Either way, the output is read.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SnapCtlResponse {
+    Sync { result: SnapCtlOutput<'static> },
+    Error { result: SnapCtlFailure },
+}
+
+/// The `result` of a [`SnapCtlResponse`] for a command that failed.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SnapCtlFailure {
+    pub value: SnapCtlOutput<'static>,
+}
+
+impl Envelope for SnapCtlResponse {
+    type Output = SnapCtlOutput<'static>;
+
+    fn into_output(self) -> Self::Output {
+        match self {
+            Self::Sync { result } => result,
+            Self::Error { result } => result.value,
+        }
+    }
+}
+
+impl SnapdClient {
+    /// Runs `snapctl` with `args`, on behalf of the snap identified by `context_id`.
+    ///
+    /// A command that fails isn't an error: check the output's
+    /// [`exit_code`](SnapCtlOutput::exit_code).
+    pub async fn snapctl(
+        &self,
+        context_id: &str,
+        args: &[&str],
+    ) -> Result<SnapCtlOutput<'static>, SnapdRequestError> {
+        self.post(&SnapCtl {
+            context_id: Cow::Borrowed(context_id),
+            args: args.iter().copied().map(Cow::Borrowed).collect(),
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_context_is_sent_in_the_body() {
+        let request = SnapCtl {
+            context_id: Cow::Borrowed("f3Vrb2tpZQ"),
+            args: vec![Cow::Borrowed("get"), Cow::Borrowed("proxy.http")],
+        };
+
+        assert_eq!(
+            serde_json::to_value(request.body()).unwrap(),
+            serde_json::json!({"context-id": "f3Vrb2tpZQ", "args": ["get", "proxy.http"]})
+        );
+        assert_eq!(
+            request.url("http://localhost".parse().unwrap()).path(),
+            "/v2/snapctl"
+        );
+    }
+}
@@ -0,0 +1,652 @@
+//! Installed snaps, via `/v2/snaps`.
+
+use std::{borrow::Cow, os::unix::ffi::OsStrExt, path::Path};
+
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use url::Url;
+
+use super::{
+    apps::AppInfo,
+    changes::{Change, ChangeId, ChangeProgress},
+    snap::{Channel, Confinement, Publisher, Revision, SnapId, SnapName, SnapStatus, SnapType},
+    time::Timestamp,
+    AsyncResponse, Get, Post, PostForm,
+};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// A snap installed on the system.
+///
+/// Some fields `snapd` sends are deliberately ignored: `developer`, which `publisher` supersedes,
+/// `icon`, `mounted-from`, `contact`, `website`, `links`, `ignore-validation`, `private`,
+/// `trymode`, `broken`, `hold`, `gating-hold` and `cohort-key`. With the `strict-decode` feature,
+/// these and any other unknown fields are rejected.
+///
+/// Like [`SnapInfo`](super::find::SnapInfo), it serializes to the same form `snapd` sends, so it
+/// can be stored and decoded again later.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "strict-decode", serde(deny_unknown_fields))]
+pub struct InstalledSnap<'a> {
+    /// The store ID, absent for snaps installed from a local file.
+    #[serde(default)]
+    pub id: Option<SnapId<'a>>,
+    pub name: SnapName<'a>,
+    #[serde(default)]
+    pub title: Option<Cow<'a, str>>,
+    pub summary: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    pub version: Cow<'a, str>,
+    pub revision: Revision,
+    /// The channel the installed revision came from.
+    #[serde(default)]
+    pub channel: Option<Channel<'a>>,
+    /// The channel the snap refreshes from, which differs from [`channel`](Self::channel) after a
+    /// [`SwitchSnap`] until the next refresh.
+    #[serde(default)]
+    pub tracking_channel: Option<Channel<'a>>,
+    pub confinement: Confinement,
+    #[serde(rename = "type")]
+    pub snap_type: SnapType,
+    #[serde(default)]
+    pub base: Option<SnapName<'a>>,
+    #[serde(default)]
+    pub publisher: Option<Publisher<'a>>,
+    pub status: SnapStatus,
+    /// The size of the snap's squashfs file, in bytes.
+    #[serde(default)]
+    pub installed_size: Option<u64>,
+    /// When the current revision was installed, absent if the snap was never activated.
+    #[serde(default)]
+    pub install_date: Option<Timestamp>,
+    #[serde(default)]
+    pub devmode: bool,
+    #[serde(default)]
+    pub jailmode: bool,
+    /// The health the snap last reported through `snapctl set-health`, if it ever did.
+    #[serde(default)]
+    pub health: Option<Health<'a>>,
+    /// Set while a refresh of the snap is held back because it's running.
+    #[serde(default)]
+    pub refresh_inhibit: Option<RefreshInhibit>,
+    /// The snap's apps and services, as [`GetApps`](super::apps::GetApps) would list them.
+    #[serde(default)]
+    pub apps: Vec<AppInfo<'a>>,
+    /// The AppStream IDs of the snap's apps, such as `org.gnome.Calculator`, for matching it to
+    /// AppStream metadata. Each app's own is its [`common_id`](AppInfo::common_id).
+    #[serde(default)]
+    pub common_ids: Vec<Cow<'a, str>>,
+}
+
+impl InstalledSnap<'_> {
+    /// Whether `other` describes the same revision of the same snap, from the same channel,
+    /// ignoring details that change while it's installed, such as its health or status.
+    pub fn same_identity(&self, other: &InstalledSnap<'_>) -> bool {
+        self.id == other.id
+            && self.name == other.name
+            && self.revision == other.revision
+            && self.channel == other.channel
+    }
+}
+
+/// A refresh held back because the snap is running, so that it isn't pulled out from under the
+/// user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RefreshInhibit {
+    /// When the refresh goes ahead anyway, if the snap is still running.
+    pub proceed_time: Timestamp,
+}
+
+/// A snap's self-reported health.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Health<'a> {
+    pub status: HealthStatus,
+    #[serde(default)]
+    pub message: Option<Cow<'a, str>>,
+    /// A machine-readable code for the problem, set alongside any status but `okay`.
+    #[serde(default)]
+    pub code: Option<Cow<'a, str>>,
+    /// The revision that reported this health.
+    pub revision: Revision,
+    /// When the health was last set.
+    pub timestamp: Timestamp,
+}
+
+/// The states a snap can report through `snapctl set-health`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Okay,
+    /// The snap is waiting on something, such as a network connection, and should recover.
+    Waiting,
+    /// The snap can't work until the user does something.
+    Blocked,
+    Error,
+    /// The snap hasn't said how it is, or it reported a status this crate doesn't know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Gets a single installed snap, via `/v2/snaps/{name}`.
+#[derive(Clone, Debug)]
+pub struct GetSnap<'a> {
+    pub name: SnapName<'a>,
+}
+
+impl Get for GetSnap<'_> {
+    type Payload = InstalledSnap<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/snaps/{}", self.name));
+        base_url
+    }
+}
+
+/// Lists the installed snaps, via `/v2/snaps`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GetSnaps;
+
+impl Get for GetSnaps {
+    type Payload = Vec<InstalledSnap<'static>>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/snaps");
+        base_url
+    }
+}
+
+/// Lists every revision of a snap kept on the system, via `/v2/snaps?select=all&snaps={name}`.
+///
+/// Besides the current revision, which is [`Active`](SnapStatus::Active), this includes the
+/// disabled revisions kept around to revert to, which are [`Installed`](SnapStatus::Installed).
+#[derive(Clone, Debug)]
+pub struct GetSnapRevisions<'a> {
+    pub name: SnapName<'a>,
+}
+
+impl Get for GetSnapRevisions<'_> {
+    type Payload = Vec<InstalledSnap<'static>>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/snaps");
+        base_url
+            .query_pairs_mut()
+            .append_pair("select", "all")
+            .append_pair("snaps", self.name.as_str());
+        base_url
+    }
+}
+
+/// Changes the channel a snap tracks without refreshing it, via a POST to `/v2/snaps/{name}`.
+///
+/// The snap moves to the new channel's revision at its next refresh.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "action", rename = "switch")]
+pub struct SwitchSnap<'a> {
+    #[serde(skip)]
+    pub name: SnapName<'a>,
+    pub channel: Channel<'a>,
+}
+
+impl Post for SwitchSnap<'_> {
+    type Body<'b>
+        = &'b Self
+    where
+        Self: 'b;
+    type Response = AsyncResponse;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/snaps/{}", self.name));
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self
+    }
+}
+
+/// Installs a snap from the store, via a POST to `/v2/snaps/{name}`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "action", rename = "install")]
+pub struct InstallSnap<'a> {
+    #[serde(skip)]
+    pub name: SnapName<'a>,
+    #[serde(flatten)]
+    pub options: InstallOptions<'a>,
+}
+
+/// How to install a snap, for [`InstallSnap`]. The defaults install the latest stable revision,
+/// strictly confined.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InstallOptions<'a> {
+    /// The channel to install from and track, rather than `latest/stable`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<Channel<'a>>,
+    /// A specific revision to install, rather than the channel's current one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<Revision>,
+    /// Installs a snap with classic confinement, which is otherwise refused.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub classic: bool,
+    /// Installs the snap in developer mode, with its confinement violations logged but allowed.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub devmode: bool,
+    /// Enforces strict confinement even for a snap asking for developer mode.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub jailmode: bool,
+    /// Installs the snap even if it has no assertions, such as a locally built snap.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dangerous: bool,
+}
+
+impl Post for InstallSnap<'_> {
+    type Body<'b>
+        = &'b Self
+    where
+        Self: 'b;
+    type Response = AsyncResponse;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/snaps/{}", self.name));
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self
+    }
+}
+
+/// Refreshes every snap with an update available, in a single change, via a POST to `/v2/snaps`.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+#[serde(tag = "action", rename = "refresh", rename_all = "kebab-case")]
+pub struct RefreshAll {
+    /// Refreshes snaps even if their new revisions fail validation.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_validation: bool,
+}
+
+impl Post for RefreshAll {
+    type Body<'b>
+        = &'b Self
+    where
+        Self: 'b;
+    type Response = AsyncResponse;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/snaps");
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self
+    }
+}
+
+/// Installs a snap from an unpacked directory, as `snap try` does, via a POST to `/v2/snaps`.
+///
+/// `snapd` mounts the directory itself rather than being sent its contents, so it must be readable
+/// by `snapd`, and changes to it show up in the installed snap straight away.
+#[derive(Clone, Debug)]
+pub struct TrySnap<'a> {
+    pub path: Cow<'a, Path>,
+    /// Installs the snap in developer mode, with its confinement violations logged but allowed.
+    pub devmode: bool,
+    /// Enforces strict confinement even for a snap asking for developer mode.
+    pub jailmode: bool,
+    /// Installs the snap even though it has no assertions. Tried snaps never have any, so `snapd`
+    /// doesn't need this for them, but accepts it.
+    pub dangerous: bool,
+}
+
+impl PostForm for TrySnap<'_> {
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/snaps");
+        base_url
+    }
+
+    fn fields(&self) -> Vec<(&'static str, Cow<'_, [u8]>)> {
+        let mut fields = vec![
+            ("action", Cow::Borrowed(&b"try"[..])),
+            ("snap-path", Cow::Borrowed(self.path.as_os_str().as_bytes())),
+        ];
+        for (name, set) in [
+            ("devmode", self.devmode),
+            ("jailmode", self.jailmode),
+            ("dangerous", self.dangerous),
+        ] {
+            if set {
+                fields.push((name, Cow::Borrowed(&b"true"[..])));
+            }
+        }
+        fields
+    }
+}
+
+/// What came of asking `snapd` to refresh snaps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RefreshOutcome {
+    /// The refresh was started, as the change with this ID.
+    Started(ChangeId),
+    /// Every snap was already up to date.
+    UpToDate,
+}
+
+impl SnapdClient {
+    /// Installs the snap `name` from the store, returning the change installing it.
+    pub async fn install(
+        &self,
+        name: SnapName<'_>,
+        options: InstallOptions<'_>,
+    ) -> Result<ChangeId, SnapdRequestError> {
+        self.post(&InstallSnap { name, options }).await
+    }
+
+    /// Installs the snap `name` from the store, and waits for the change installing it to be ready.
+    pub async fn install_and_wait(
+        &self,
+        name: SnapName<'_>,
+        options: InstallOptions<'_>,
+    ) -> Result<Change<'static>, SnapdRequestError> {
+        let id = self.install(name, options).await?;
+        self.wait_for_change(&id).await
+    }
+
+    /// Installs the snap `name` from the store, like [`install_and_wait`](Self::install_and_wait),
+    /// sending the change's progress to `progress` as it goes.
+    pub async fn install_and_wait_with_progress(
+        &self,
+        name: SnapName<'_>,
+        options: InstallOptions<'_>,
+        progress: mpsc::Sender<ChangeProgress>,
+    ) -> Result<Change<'static>, SnapdRequestError> {
+        let id = self.install(name, options).await?;
+        self.wait_for_change_with_progress(&id, progress).await
+    }
+
+    /// Installs a snap from the unpacked directory `path`, as `snap try` does, returning the change
+    /// installing it.
+    pub async fn try_snap(&self, path: &Path) -> Result<ChangeId, SnapdRequestError> {
+        self.post_form(&TrySnap {
+            path: Cow::Borrowed(path),
+            devmode: false,
+            jailmode: false,
+            dangerous: false,
+        })
+        .await
+    }
+
+    /// Whether the snap `name` is installed.
+    pub async fn is_installed(&self, name: SnapName<'_>) -> Result<bool, SnapdRequestError> {
+        match self.get(&GetSnap { name }).await {
+            Ok(_) => Ok(true),
+            Err(SnapdRequestError::Snapd { status, .. }) if status == StatusCode::NOT_FOUND => {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether `revision` of the snap `name` is kept on the system, and so can be reverted to.
+    pub async fn has_revision(
+        &self,
+        name: SnapName<'_>,
+        revision: Revision,
+    ) -> Result<bool, SnapdRequestError> {
+        let revisions = self.get(&GetSnapRevisions { name }).await?;
+        Ok(revisions.iter().any(|snap| snap.revision == revision))
+    }
+
+    /// Refreshes every snap with an update available.
+    ///
+    /// Older versions of `snapd` fail with a `snap-no-update-available` error when there's nothing
+    /// to refresh, which is returned as [`RefreshOutcome::UpToDate`]. Newer ones start a change
+    /// that's ready straight away instead, which is returned as [`RefreshOutcome::Started`].
+    pub async fn refresh_all(&self) -> Result<RefreshOutcome, SnapdRequestError> {
+        match self.post(&RefreshAll::default()).await {
+            Ok(id) => Ok(RefreshOutcome::Started(id)),
+            Err(SnapdRequestError::Snapd { error, .. })
+                if error.kind.as_deref() == Some("snap-no-update-available") =>
+            {
+                Ok(RefreshOutcome::UpToDate)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decode_result;
+
+    #[test]
+    fn blocked_health_is_decoded() {
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_health_blocked.json"));
+        let health = snap.health.expect("snap reported its health");
+        assert_eq!(health.status, HealthStatus::Blocked);
+        assert_eq!(
+            health.message.as_deref(),
+            Some("no broker configuration found")
+        );
+        assert_eq!(health.code.as_deref(), Some("needs-config"));
+        assert_eq!(health.revision, Revision::new(1004));
+    }
+
+    #[test]
+    fn sizes_and_install_dates_are_decoded() {
+        let snaps: Vec<InstalledSnap> = decode_result(fixture!("get_snaps.json"));
+
+        assert_eq!(snaps[0].installed_size, Some(5_368_709_120));
+        assert!(snaps[0].install_date.is_some());
+        // A snap installed from a file, and never activated, has neither.
+        assert_eq!(snaps[1].installed_size, None);
+        assert!(snaps[1].install_date.is_none());
+    }
+
+    #[test]
+    fn switch_sends_only_the_channel() {
+        let switch = SwitchSnap {
+            name: "hello".into(),
+            channel: "latest/edge".into(),
+        };
+        assert_eq!(
+            switch.url(Url::parse("http://localhost").unwrap()).path(),
+            "/v2/snaps/hello"
+        );
+        assert_eq!(
+            serde_json::to_value(switch.body()).unwrap(),
+            serde_json::json!({"action": "switch", "channel": "latest/edge"})
+        );
+    }
+
+    #[test]
+    fn channel_and_tracking_channel_can_differ() {
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_switched.json"));
+
+        assert_eq!(snap.channel, Some(Channel::new("5.21/stable")));
+        assert_eq!(snap.tracking_channel, Some(Channel::new("latest/edge")));
+        assert_ne!(snap.channel, snap.tracking_channel);
+    }
+
+    #[cfg(not(feature = "strict-decode"))]
+    #[test]
+    fn unmodelled_fields_are_ignored() {
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_unmodelled_fields.json"));
+        assert_eq!(snap.name.as_str(), "lxd");
+    }
+
+    #[cfg(feature = "strict-decode")]
+    #[test]
+    fn strict_decode_rejects_unmodelled_fields() {
+        let error = serde_json::from_str::<crate::api::SyncResponse<InstalledSnap>>(fixture!(
+            "get_snap_unmodelled_fields.json"
+        ))
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field"), "{error}");
+    }
+
+    #[test]
+    fn refresh_all_names_no_snaps() {
+        assert_eq!(
+            serde_json::to_value(RefreshAll::default().body()).unwrap(),
+            serde_json::json!({"action": "refresh"})
+        );
+        let ignoring = RefreshAll {
+            ignore_validation: true,
+        };
+        assert_eq!(
+            serde_json::to_value(ignoring.body()).unwrap(),
+            serde_json::json!({"action": "refresh", "ignore-validation": true})
+        );
+    }
+
+    #[test]
+    fn revisions_are_listed_for_one_snap() {
+        let revisions = GetSnapRevisions {
+            name: "hello".into(),
+        };
+        assert_eq!(
+            revisions
+                .url(Url::parse("http://localhost").unwrap())
+                .as_str(),
+            "http://localhost/v2/snaps?select=all&snaps=hello"
+        );
+    }
+
+    #[test]
+    fn refresh_inhibited_snap_says_when_it_proceeds() {
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_refresh_inhibited.json"));
+
+        let inhibit = snap.refresh_inhibit.expect("refresh is inhibited");
+        assert_eq!(
+            inhibit.proceed_time,
+            "2024-05-14T06:12:45.123456789Z".parse().unwrap()
+        );
+
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_switched.json"));
+        assert!(snap.refresh_inhibit.is_none());
+    }
+
+    #[test]
+    fn try_sends_only_the_path_and_set_flags() {
+        let try_snap = TrySnap {
+            path: Cow::Borrowed(Path::new("/home/dev/hello/prime")),
+            devmode: true,
+            jailmode: false,
+            dangerous: false,
+        };
+        assert_eq!(
+            try_snap.url(Url::parse("http://localhost").unwrap()).path(),
+            "/v2/snaps"
+        );
+        assert_eq!(
+            try_snap.fields(),
+            [
+                ("action", Cow::Borrowed(&b"try"[..])),
+                ("snap-path", Cow::Borrowed(&b"/home/dev/hello/prime"[..])),
+                ("devmode", Cow::Borrowed(&b"true"[..])),
+            ]
+        );
+    }
+
+    #[test]
+    fn apps_are_listed_inline() {
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_two_apps.json"));
+
+        let [daemon, lxc] = &snap.apps[..] else {
+            panic!("expected two apps, found {:?}", snap.apps);
+        };
+        assert_eq!(daemon.name.as_str(), "daemon");
+        assert!(daemon.is_service());
+        assert!(daemon.active);
+        assert_eq!(lxc.name.as_str(), "lxc");
+        assert!(!lxc.is_service());
+        assert_eq!(lxc.common_id.as_deref(), Some("org.linuxcontainers.lxc"));
+    }
+
+    #[test]
+    fn common_ids_and_desktop_files_are_read() {
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_common_ids.json"));
+
+        assert_eq!(
+            snap.common_ids,
+            [
+                "org.gnome.Calculator",
+                "org.gnome.Calculator.SearchProvider"
+            ]
+        );
+        assert_eq!(
+            snap.apps[0].desktop_file.as_deref(),
+            Some("/var/lib/snapd/desktop/applications/gnome-calculator_gnome-calculator.desktop")
+        );
+        assert_eq!(
+            snap.apps[0].common_id.as_deref(),
+            Some("org.gnome.Calculator")
+        );
+    }
+
+    #[test]
+    fn dangerous_is_only_sent_when_set() {
+        let install = |dangerous| InstallSnap {
+            name: "hello".into(),
+            options: InstallOptions {
+                dangerous,
+                ..Default::default()
+            },
+        };
+
+        assert_eq!(
+            serde_json::to_string(install(true).body()).unwrap(),
+            r#"{"action":"install","dangerous":true}"#
+        );
+        assert_eq!(
+            serde_json::to_string(install(false).body()).unwrap(),
+            r#"{"action":"install"}"#
+        );
+
+        let try_snap = TrySnap {
+            path: Cow::Borrowed(Path::new("/home/me/hello/prime")),
+            devmode: false,
+            jailmode: false,
+            dangerous: true,
+        };
+        assert!(try_snap
+            .fields()
+            .contains(&("dangerous", Cow::Borrowed(&b"true"[..]))));
+    }
+
+    #[test]
+    fn same_identity_ignores_the_install_date() {
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_two_apps.json"));
+        let mut reinstalled = snap.clone();
+        reinstalled.install_date = Some("2024-06-01T10:00:00Z".parse().unwrap());
+
+        assert!(snap.same_identity(&reinstalled));
+
+        reinstalled.revision = Revision::new(28464);
+        assert!(!snap.same_identity(&reinstalled));
+    }
+
+    #[test]
+    fn installed_snap_round_trips() {
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_two_apps.json"));
+
+        let serialized = serde_json::to_value(&snap).unwrap();
+        let decoded: InstalledSnap = serde_json::from_value(serialized.clone()).unwrap();
+
+        assert!(decoded.same_identity(&snap));
+        assert_eq!(decoded.apps.len(), 2);
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serialized);
+    }
+
+    #[test]
+    fn unrecognized_health_is_unknown() {
+        let snap: InstalledSnap = decode_result(fixture!("get_snap_health_unrecognized.json"));
+        let health = snap.health.expect("snap reported its health");
+        assert_eq!(health.status, HealthStatus::Unknown);
+        assert_eq!(health.code.as_deref(), Some("broker-degraded"));
+    }
+}
@@ -0,0 +1,108 @@
+//! Snapshots of snaps' data, via `/v2/snapshots`.
+
+use std::{borrow::Cow, fmt};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{snap::SnapName, GetStream, PostBytes, SyncResponse};
+use crate::{ResponseStream, SnapdClient, SnapdRequestError};
+
+/// The ID of a snapshot set, the snapshots of one or more snaps saved together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SnapshotSetId(pub u64);
+
+impl fmt::Display for SnapshotSetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Exports a snapshot set as an archive, via `/v2/snapshots/{set}/export`.
+///
+/// The archive can be large, so it's streamed rather than buffered, and can be written out as it
+/// arrives:
+///
+/// ```no_run
+/// # async fn f(client: snapd::SnapdClient) -> Result<(), Box<dyn std::error::Error>> {
+/// use std::{fs::File, io::Write};
+///
+/// use snapd::api::snapshots::{ExportSnapshot, SnapshotSetId};
+///
+/// let mut file = File::create("snapshot.tar")?;
+/// let mut export = client.get_stream(&ExportSnapshot { set: SnapshotSetId(1) }).await?;
+/// while let Some(chunk) = export.chunk().await? {
+///     file.write_all(&chunk)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ExportSnapshot {
+    pub set: SnapshotSetId,
+}
+
+impl GetStream for ExportSnapshot {
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/snapshots/{}/export", self.set));
+        base_url
+    }
+}
+
+/// Imports a snapshot set from an archive made by [`ExportSnapshot`], via a POST to
+/// `/v2/snapshots`.
+///
+/// The set is given a new ID, as the one it was exported with may already be taken.
+#[derive(Clone, Debug)]
+pub struct ImportSnapshot<'a> {
+    pub data: Cow<'a, [u8]>,
+}
+
+impl PostBytes for ImportSnapshot<'_> {
+    const CONTENT_TYPE: &'static str = "application/x.snapd.snapshot";
+
+    type Response = SyncResponse<ImportedSnapshot<'static>>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/snapshots");
+        base_url
+    }
+
+    fn body(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.data)
+    }
+}
+
+/// A snapshot set imported by [`ImportSnapshot`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ImportedSnapshot<'a> {
+    /// The ID the set was imported with.
+    pub set_id: SnapshotSetId,
+    /// The snaps the set holds snapshots of.
+    #[serde(default)]
+    pub snaps: Vec<SnapName<'a>>,
+}
+
+impl SnapdClient {
+    /// Exports the snapshot set `set`, returning the archive to be read as it arrives.
+    pub async fn export_snapshot(
+        &self,
+        set: SnapshotSetId,
+    ) -> Result<ResponseStream, SnapdRequestError> {
+        self.get_stream(&ExportSnapshot { set }).await
+    }
+
+    /// Imports a snapshot set from the archive `data`, made by
+    /// [`export_snapshot`](Self::export_snapshot).
+    pub async fn import_snapshot(
+        &self,
+        data: &[u8],
+    ) -> Result<ImportedSnapshot<'static>, SnapdRequestError> {
+        self.post_bytes(&ImportSnapshot {
+            data: Cow::Borrowed(data),
+        })
+        .await
+    }
+}
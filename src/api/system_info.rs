@@ -0,0 +1,259 @@
+//! Information about `snapd` and the system it runs on, via `/v2/system-info`.
+
+use std::{borrow::Cow, collections::HashMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::Url;
+
+use super::Get;
+use crate::{SnapdClient, SnapdRequestError};
+
+/// `snapd`'s version, and the system it's running on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SystemInfo<'a> {
+    pub series: Cow<'a, str>,
+    /// `snapd`'s version.
+    pub version: Cow<'a, str>,
+    #[serde(default)]
+    pub build_id: Option<Cow<'a, str>>,
+    pub os_release: OsRelease<'a>,
+    /// Whether this is a classic distribution, rather than Ubuntu Core.
+    #[serde(default)]
+    pub on_classic: bool,
+    /// Whether a user has been created, or the device is otherwise managed.
+    #[serde(default)]
+    pub managed: bool,
+    #[serde(default)]
+    pub kernel_version: Option<Cow<'a, str>>,
+    #[serde(default)]
+    pub architecture: Option<Cow<'a, str>>,
+    /// The virtualization the system runs under, such as `kvm` or `lxc`, if any.
+    #[serde(default)]
+    pub virtualization: Option<Cow<'a, str>>,
+    #[serde(default)]
+    pub confinement: Option<SystemConfinement>,
+    /// What each sandboxing backend, such as `apparmor` or `seccomp`, supports on this system.
+    #[serde(default)]
+    pub sandbox_features: HashMap<Cow<'a, str>, Vec<Cow<'a, str>>>,
+    /// Experimental features, keyed by name, and whether they're supported and enabled.
+    #[serde(default)]
+    pub features: HashMap<Cow<'a, str>, FeatureStatus<'a>>,
+}
+
+impl SystemInfo<'_> {
+    /// [`version`](Self::version), parsed so it can be compared.
+    pub fn snapd_version(&self) -> Result<SnapdVersion, ParseVersionError> {
+        self.version.parse()
+    }
+
+    /// The distribution's `ID` from `os-release`, such as `ubuntu` or `fedora`.
+    pub fn distro_id(&self) -> &str {
+        &self.os_release.id
+    }
+
+    /// Whether this is Ubuntu, classic or Core.
+    pub fn is_ubuntu(&self) -> bool {
+        matches!(self.distro_id(), "ubuntu" | "ubuntu-core")
+    }
+}
+
+/// The distribution, as described by its `os-release` file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OsRelease<'a> {
+    /// The distribution's `ID`, such as `ubuntu`.
+    pub id: Cow<'a, str>,
+    /// The distribution's `VERSION_ID`, such as `24.04`.
+    #[serde(default)]
+    pub version_id: Option<Cow<'a, str>>,
+}
+
+/// Whether an experimental feature can be, and is, used.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FeatureStatus<'a> {
+    pub supported: bool,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub unsupported_reason: Option<Cow<'a, str>>,
+}
+
+/// How fully snaps can be confined on this system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemConfinement {
+    Strict,
+    /// Some of the sandbox is unavailable, such as on distributions without AppArmor.
+    Partial,
+}
+
+/// Gets information about `snapd` and the system, via `/v2/system-info`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GetSystemInfo;
+
+impl Get for GetSystemInfo {
+    type Payload = SystemInfo<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/system-info");
+        base_url
+    }
+}
+
+/// A version of `snapd`, such as `2.61.1`, which can be compared with others.
+///
+/// Versions are parsed leniently, since distributions package `snapd` with suffixes such as
+/// `+ubuntu20.04.1` or `+git1234.abcdef`, which are ignored. A missing patch version is zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SnapdVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl SnapdVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for SnapdVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The error returned when a string isn't a valid [`SnapdVersion`].
+#[derive(Clone, Debug, Error)]
+#[error("invalid snapd version {0:?}")]
+pub struct ParseVersionError(String);
+
+impl FromStr for SnapdVersion {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseVersionError(s.to_owned());
+
+        // Everything from the first character that isn't part of the numbers, such as the `+` of
+        // `+git` or the `~` of `~rc1`, is a suffix.
+        let end = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let mut parts = s[..end].split('.').map(|part| part.parse::<u32>());
+
+        let mut next = |required: bool| match parts.next() {
+            Some(Ok(part)) => Ok(part),
+            None if !required => Ok(0),
+            _ => Err(invalid()),
+        };
+        Ok(Self::new(next(true)?, next(true)?, next(false)?))
+    }
+}
+
+/// The ways finding out `snapd`'s version can fail.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SnapdVersionError {
+    #[error(transparent)]
+    Request(#[from] SnapdRequestError),
+    #[error(transparent)]
+    Parse(#[from] ParseVersionError),
+}
+
+impl SnapdClient {
+    /// Gets information about `snapd` and the system it's running on.
+    pub async fn system_info(&self) -> Result<SystemInfo<'static>, SnapdRequestError> {
+        self.get(&GetSystemInfo).await
+    }
+
+    /// Gets the version of `snapd`.
+    pub async fn snapd_version(&self) -> Result<SnapdVersion, SnapdVersionError> {
+        Ok(self.system_info().await?.snapd_version()?)
+    }
+
+    /// Whether `snapd` is at least `version`, for deciding whether to use newer features.
+    ///
+    /// A version that can't be parsed, such as that of a development build, is assumed to be new
+    /// enough.
+    pub(crate) async fn snapd_is_at_least(
+        &self,
+        version: SnapdVersion,
+    ) -> Result<bool, SnapdRequestError> {
+        match self.snapd_version().await {
+            Ok(current) => Ok(current >= version),
+            Err(SnapdVersionError::Request(e)) => Err(e),
+            Err(SnapdVersionError::Parse(_)) => Ok(true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decode_result;
+
+    #[test]
+    fn ubuntu_os_release() {
+        let info: SystemInfo<'static> = decode_result(fixture!("system_info.json"));
+
+        assert_eq!(info.distro_id(), "ubuntu");
+        assert!(info.is_ubuntu());
+        assert_eq!(info.os_release.version_id.as_deref(), Some("24.04"));
+        assert!(info.on_classic);
+    }
+
+    #[test]
+    fn apparmor_sandbox_features() {
+        let info: SystemInfo<'static> = decode_result(fixture!("system_info.json"));
+
+        let apparmor = &info.sandbox_features["apparmor"];
+        assert!(apparmor.iter().any(|feature| feature == "kernel:caps"));
+        assert_eq!(apparmor.len(), 7);
+        assert_eq!(info.confinement, Some(SystemConfinement::Strict));
+        assert!(info.features["parallel-instances"].supported);
+    }
+
+    fn version(s: &str) -> SnapdVersion {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn versions_with_suffixes() {
+        assert_eq!(version("2.61.3+git123.abc"), SnapdVersion::new(2, 61, 3));
+        assert_eq!(version("2.61.3+ubuntu24.04"), SnapdVersion::new(2, 61, 3));
+        assert_eq!(version("2.59"), SnapdVersion::new(2, 59, 0));
+        assert_eq!(version("2.61~pre1"), SnapdVersion::new(2, 61, 0));
+        assert_eq!(version("2.61~pre1").to_string(), "2.61.0");
+    }
+
+    #[test]
+    fn malformed_versions() {
+        for s in [
+            "",
+            "2",
+            "two.59",
+            "2..1",
+            "+git123",
+            "2.x.1",
+            "4294967296.1",
+        ] {
+            assert!(s.parse::<SnapdVersion>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn versions_compare_at_the_boundary() {
+        let notices = SnapdVersion::new(2, 59, 0);
+        assert!(version("2.59") >= notices);
+        assert!(version("2.59~rc1") >= notices);
+        assert!(version("2.58.99") < notices);
+        assert!(version("2.100") > notices);
+    }
+}
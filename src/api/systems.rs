@@ -0,0 +1,165 @@
+//! Ubuntu Core's recovery systems, via `/v2/systems`.
+
+use std::borrow::Cow;
+
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use url::Url;
+
+use super::{snap::Publisher, Get, Post, SyncResponse};
+use crate::{SnapdClient, SnapdRequestError};
+
+/// The recovery systems on the device.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Systems<'a> {
+    #[serde(default)]
+    pub systems: Vec<System<'a>>,
+}
+
+/// A recovery system, which the device can be rebooted into.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct System<'a> {
+    /// The system's label, usually the date it was created.
+    pub label: Cow<'a, str>,
+    /// Whether the device is running this system's model.
+    #[serde(default)]
+    pub current: bool,
+    pub model: SystemModel<'a>,
+    pub brand: Publisher<'a>,
+    /// The modes the device can be rebooted into using this system.
+    #[serde(default)]
+    pub actions: Vec<SystemAction<'a>>,
+}
+
+/// The model assertion a recovery system was seeded with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SystemModel<'a> {
+    pub model: Cow<'a, str>,
+    pub brand_id: Cow<'a, str>,
+    #[serde(default)]
+    pub display_name: Option<Cow<'a, str>>,
+}
+
+/// A mode a recovery system can be booted in, with the title offered to users.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemAction<'a> {
+    pub title: Cow<'a, str>,
+    pub mode: SystemMode,
+}
+
+/// The modes an Ubuntu Core device can boot in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SystemMode {
+    /// Wipes the device and installs the system afresh.
+    Install,
+    /// Boots the recovery system without touching the installed one, to rescue data.
+    Recover,
+    /// Boots normally.
+    Run,
+    /// Resets the device to the state it was installed in, keeping its identity.
+    FactoryReset,
+    /// A mode this crate doesn't know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Lists the device's recovery systems, via `/v2/systems`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GetSystems;
+
+impl Get for GetSystems {
+    type Payload = Systems<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/systems");
+        base_url
+    }
+}
+
+/// Reboots the device into the recovery system `label`, via a POST to `/v2/systems/{label}`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "action", rename = "reboot")]
+pub struct RebootToSystem<'a> {
+    #[serde(skip)]
+    pub label: Cow<'a, str>,
+    pub mode: SystemMode,
+}
+
+impl Post for RebootToSystem<'_> {
+    type Body<'b>
+        = &'b Self
+    where
+        Self: 'b;
+    type Response = SyncResponse<IgnoredAny>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path(&format!("/v2/systems/{}", self.label));
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self
+    }
+}
+
+impl SnapdClient {
+    /// Reboots the device into the recovery system `label`, in `mode`.
+    ///
+    /// `snapd` responds once the reboot is scheduled, rather than starting a change.
+    pub async fn reboot_to_system(
+        &self,
+        label: &str,
+        mode: SystemMode,
+    ) -> Result<(), SnapdRequestError> {
+        self.post(&RebootToSystem {
+            label: label.into(),
+            mode,
+        })
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decode_result;
+
+    #[test]
+    fn systems_are_decoded() {
+        let systems: Systems = decode_result(fixture!("systems.json"));
+        assert_eq!(systems.systems.len(), 1);
+
+        let system = &systems.systems[0];
+        assert_eq!(system.label, "20240501");
+        assert!(system.current);
+        assert_eq!(system.model.model, "ubuntu-core-22-amd64");
+        let modes: Vec<_> = system.actions.iter().map(|action| action.mode).collect();
+        assert_eq!(
+            modes,
+            [
+                SystemMode::Install,
+                SystemMode::Recover,
+                SystemMode::Run,
+                SystemMode::Unknown
+            ]
+        );
+    }
+
+    #[test]
+    fn reboot_body_names_the_mode() {
+        let reboot = RebootToSystem {
+            label: "20240501".into(),
+            mode: SystemMode::FactoryReset,
+        };
+        assert_eq!(
+            reboot.url(Url::parse("http://localhost").unwrap()).path(),
+            "/v2/systems/20240501"
+        );
+        assert_eq!(
+            serde_json::to_value(reboot.body()).unwrap(),
+            serde_json::json!({"action": "reboot", "mode": "factory-reset"})
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Desktop themes packaged as snaps, via `/v2/accessories/themes`.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{AsyncResponse, Get, Post};
+
+/// The name of a desktop theme, e.g. `Yaru-dark`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ThemeName<'a>(Cow<'a, str>);
+
+cow_str_newtype!(ThemeName);
+
+/// Whether a theme is available as a snap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeStatus {
+    /// A snap providing the theme is installed.
+    Installed,
+    /// A snap providing the theme can be installed from the store.
+    Available,
+    /// No snap provides the theme.
+    Unavailable,
+}
+
+/// The status of each theme asked about, by kind.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ThemeStatuses<'a> {
+    #[serde(default)]
+    pub gtk_themes: HashMap<ThemeName<'a>, ThemeStatus>,
+    #[serde(default)]
+    pub icon_themes: HashMap<ThemeName<'a>, ThemeStatus>,
+    #[serde(default)]
+    pub sound_themes: HashMap<ThemeName<'a>, ThemeStatus>,
+}
+
+/// Checks which themes are installed or available as snaps, via `/v2/accessories/themes`.
+///
+/// Only the themes asked for are included in the result.
+#[derive(Clone, Debug, Default)]
+pub struct CheckThemes<'a> {
+    pub gtk: Vec<ThemeName<'a>>,
+    pub icon: Vec<ThemeName<'a>>,
+    pub sound: Vec<ThemeName<'a>>,
+}
+
+impl Get for CheckThemes<'_> {
+    type Payload = ThemeStatuses<'static>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/accessories/themes");
+        {
+            let mut query = base_url.query_pairs_mut();
+            for (key, themes) in [
+                ("gtk-theme", &self.gtk),
+                ("icon-theme", &self.icon),
+                ("sound-theme", &self.sound),
+            ] {
+                for theme in themes {
+                    query.append_pair(key, theme.as_str());
+                }
+            }
+        }
+        if base_url.query() == Some("") {
+            base_url.set_query(None);
+        }
+        base_url
+    }
+}
+
+/// Installs the snaps providing themes, via a POST to `/v2/accessories/themes`.
+///
+/// [`CheckThemes`] tells which themes are [`Available`](ThemeStatus::Available) to install.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct InstallThemes<'a> {
+    #[serde(rename = "gtk-themes", skip_serializing_if = "Vec::is_empty")]
+    pub gtk: Vec<ThemeName<'a>>,
+    #[serde(rename = "icon-themes", skip_serializing_if = "Vec::is_empty")]
+    pub icon: Vec<ThemeName<'a>>,
+    #[serde(rename = "sound-themes", skip_serializing_if = "Vec::is_empty")]
+    pub sound: Vec<ThemeName<'a>>,
+}
+
+impl Post for InstallThemes<'_> {
+    type Body<'b>
+        = &'b Self
+    where
+        Self: 'b;
+    type Response = AsyncResponse;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/accessories/themes");
+        base_url
+    }
+
+    fn body(&self) -> Self::Body<'_> {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decode_result;
+
+    #[test]
+    fn check_themes_asks_for_each_theme() {
+        let check = CheckThemes {
+            gtk: vec!["Yaru-dark".into(), "Arc".into()],
+            icon: vec!["Paper".into()],
+            sound: Vec::new(),
+        };
+        assert_eq!(
+            check.url(Url::parse("http://localhost").unwrap()).as_str(),
+            "http://localhost/v2/accessories/themes?gtk-theme=Yaru-dark&gtk-theme=Arc&icon-theme=Paper"
+        );
+    }
+
+    #[test]
+    fn installed_and_available_themes() {
+        let statuses: ThemeStatuses = decode_result(fixture!("check_themes.json"));
+
+        assert_eq!(
+            statuses.gtk_themes[&ThemeName::from("Yaru-dark")],
+            ThemeStatus::Installed
+        );
+        assert_eq!(
+            statuses.gtk_themes[&ThemeName::from("Arc")],
+            ThemeStatus::Available
+        );
+        assert_eq!(
+            statuses.icon_themes[&ThemeName::from("Paper")],
+            ThemeStatus::Unavailable
+        );
+        assert!(statuses.sound_themes.is_empty());
+    }
+
+    #[test]
+    fn install_body_leaves_out_kinds_with_no_themes() {
+        let install = InstallThemes {
+            gtk: vec!["Arc".into()],
+            icon: Vec::new(),
+            sound: vec!["Yaru".into()],
+        };
+        assert_eq!(
+            serde_json::to_value(install.body()).unwrap(),
+            serde_json::json!({"gtk-themes": ["Arc"], "sound-themes": ["Yaru"]})
+        );
+    }
+}
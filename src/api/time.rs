@@ -0,0 +1,244 @@
+//! Times as `snapd` formats them.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// A point in time reported by `snapd`, which formats them as RFC 3339.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Timestamp(pub DateTime<Utc>);
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(time: DateTime<Utc>) -> Self {
+        Self(time)
+    }
+}
+
+impl From<Timestamp> for DateTime<Utc> {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.0
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.to_rfc3339())
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = chrono::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DateTime::parse_from_rfc3339(s).map(|time| Self(time.to_utc()))
+    }
+}
+
+/// A length of time as `snapd` formats it, which is the way Go does, e.g. `1h30m15s` or `500ms`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SnapdDuration(pub Duration);
+
+impl From<Duration> for SnapdDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<SnapdDuration> for Duration {
+    fn from(duration: SnapdDuration) -> Self {
+        duration.0
+    }
+}
+
+impl fmt::Display for SnapdDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.as_nanos();
+        if nanos == 0 {
+            return f.write_str("0s");
+        }
+
+        // Like Go, durations under a second are written in the largest unit that keeps them at
+        // least one, and longer ones in hours, minutes and seconds.
+        if nanos < 1_000_000_000 {
+            let (unit, scale, digits) = match nanos {
+                0..1_000 => ("ns", 1, 0),
+                1_000..1_000_000 => ("µs", 1_000, 3),
+                _ => ("ms", 1_000_000, 6),
+            };
+            write!(f, "{}", nanos / scale)?;
+            write_fraction(f, nanos % scale, digits)?;
+            return f.write_str(unit);
+        }
+
+        let secs = self.0.as_secs();
+        let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+        if hours > 0 {
+            write!(f, "{hours}h")?;
+        }
+        if hours > 0 || minutes > 0 {
+            write!(f, "{minutes}m")?;
+        }
+        write!(f, "{secs}")?;
+        write_fraction(f, self.0.subsec_nanos().into(), 9)?;
+        f.write_str("s")
+    }
+}
+
+/// Writes the fractional part `value`, of `digits` digits, as a decimal point and the digits
+/// without trailing zeros, or nothing if it's zero.
+fn write_fraction(f: &mut fmt::Formatter<'_>, value: u128, digits: usize) -> fmt::Result {
+    if value == 0 {
+        return Ok(());
+    }
+    f.write_str(format!(".{value:0digits$}").trim_end_matches('0'))
+}
+
+/// The error returned when a string isn't a valid [`SnapdDuration`].
+#[derive(Clone, Debug, Error)]
+#[error("invalid duration {0:?}")]
+pub struct ParseDurationError(String);
+
+impl FromStr for SnapdDuration {
+    type Err = ParseDurationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseDurationError(s.to_owned());
+
+        let mut rest = s.strip_prefix('+').unwrap_or(s);
+        if rest == "0" {
+            return Ok(Self::default());
+        }
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut total: u128 = 0;
+        while !rest.is_empty() {
+            let number_end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .ok_or_else(invalid)?;
+            let (number, after) = rest.split_at(number_end);
+            let unit_end = after
+                .find(|c: char| c.is_ascii_digit() || c == '.')
+                .unwrap_or(after.len());
+            let (unit, after) = after.split_at(unit_end);
+            rest = after;
+
+            let scale: u128 = match unit {
+                "ns" => 1,
+                "us" | "µs" | "μs" => 1_000,
+                "ms" => 1_000_000,
+                "s" => 1_000_000_000,
+                "m" => 60_000_000_000,
+                "h" => 3_600_000_000_000,
+                _ => return Err(invalid()),
+            };
+
+            let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+            if (whole.is_empty() && fraction.is_empty()) || fraction.contains('.') {
+                return Err(invalid());
+            }
+            let whole: u128 = match whole {
+                "" => 0,
+                whole => whole.parse().map_err(|_| invalid())?,
+            };
+            // Digits past nanosecond precision in even the smallest unit don't matter.
+            let fraction = &fraction[..fraction.len().min(18)];
+            let fraction_nanos = match fraction {
+                "" => 0,
+                fraction => {
+                    let digits: u128 = fraction.parse().map_err(|_| invalid())?;
+                    digits * scale / 10u128.pow(fraction.len() as u32)
+                }
+            };
+
+            total = whole
+                .checked_mul(scale)
+                .and_then(|nanos| nanos.checked_add(fraction_nanos))
+                .and_then(|nanos| nanos.checked_add(total))
+                .ok_or_else(invalid)?;
+        }
+
+        let secs = u64::try_from(total / 1_000_000_000).map_err(|_| invalid())?;
+        Ok(Self(Duration::new(secs, (total % 1_000_000_000) as u32)))
+    }
+}
+
+impl Serialize for SnapdDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SnapdDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DurationVisitor;
+
+        impl de::Visitor<'_> for DurationVisitor {
+            type Value = SnapdDuration;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a duration, such as 1h30m")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<SnapdDuration, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(DurationVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duration(s: &str) -> Duration {
+        s.parse::<SnapdDuration>().unwrap().0
+    }
+
+    #[test]
+    fn durations_round_trip() {
+        for (text, expected) in [
+            ("0s", Duration::ZERO),
+            ("30s", Duration::from_secs(30)),
+            ("1h30m15s", Duration::from_secs(5415)),
+            ("1h0m0s", Duration::from_secs(3600)),
+            ("2m0.5s", Duration::from_millis(120_500)),
+            ("500ms", Duration::from_millis(500)),
+            ("1.5µs", Duration::from_nanos(1500)),
+            ("7ns", Duration::from_nanos(7)),
+        ] {
+            assert_eq!(duration(text), expected, "{text}");
+            assert_eq!(SnapdDuration(expected).to_string(), text);
+        }
+    }
+
+    #[test]
+    fn durations_in_other_forms_are_accepted() {
+        assert_eq!(duration("1h30m"), Duration::from_secs(5400));
+        assert_eq!(duration("90m"), Duration::from_secs(5400));
+        assert_eq!(duration("1.5h"), Duration::from_secs(5400));
+        assert_eq!(duration("+10s"), Duration::from_secs(10));
+        assert_eq!(duration("0"), Duration::ZERO);
+        assert_eq!(duration("3us"), Duration::from_micros(3));
+        assert_eq!(duration(".5s"), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn malformed_durations_are_rejected() {
+        for text in ["", "10", "1x", "s", "1..5s", "-1s", "1h 30m"] {
+            assert!(text.parse::<SnapdDuration>().is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn durations_are_strings_in_json() {
+        let duration: SnapdDuration = serde_json::from_str(r#""1h30m15s""#).unwrap();
+        assert_eq!(serde_json::to_string(&duration).unwrap(), r#""1h30m15s""#);
+    }
+}
@@ -0,0 +1,81 @@
+//! Warnings `snapd` has for the user, via `/v2/warnings`.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    time::{SnapdDuration, Timestamp},
+    Get,
+};
+
+/// Something `snapd` warns about, such as a snap it couldn't refresh.
+///
+/// A warning is identified by its message. When `snapd` warns about the same thing again, the
+/// existing warning is updated rather than a new one being added.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Warning<'a> {
+    pub message: Cow<'a, str>,
+    pub first_added: Timestamp,
+    pub last_added: Timestamp,
+    /// When the warning was last shown to the user, or `None` if it never has been.
+    #[serde(default)]
+    pub last_shown: Option<Timestamp>,
+    /// How long after it was last added the warning is forgotten.
+    pub expire_after: SnapdDuration,
+    /// How long after it was last shown the warning is shown again.
+    pub repeat_after: SnapdDuration,
+}
+
+impl Warning<'_> {
+    /// When `snapd` forgets the warning, unless it's added again first.
+    pub fn expires_at(&self) -> Timestamp {
+        Timestamp(self.last_added.0 + self.expire_after.0)
+    }
+}
+
+/// Lists warnings, via `/v2/warnings`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GetWarnings {
+    /// Whether to list every warning, rather than only those due to be shown, which haven't been
+    /// shown in their [`repeat_after`](Warning::repeat_after).
+    pub all: bool,
+}
+
+impl Get for GetWarnings {
+    type Payload = Vec<Warning<'static>>;
+
+    fn url(&self, mut base_url: Url) -> Url {
+        base_url.set_path("/v2/warnings");
+        if self.all {
+            base_url.query_pairs_mut().append_pair("select", "all");
+        }
+        base_url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::api::decode_result;
+
+    #[test]
+    fn durations_are_decoded() {
+        let warnings: Vec<Warning> = decode_result(fixture!("warnings.json"));
+        let warning = &warnings[0];
+
+        assert_eq!(warning.repeat_after.0, Duration::from_secs(24 * 60 * 60));
+        assert_eq!(
+            warning.expire_after.0,
+            Duration::from_secs(28 * 24 * 60 * 60)
+        );
+        assert_eq!(
+            warning.expires_at(),
+            "2024-05-30T10:00:00Z".parse().unwrap()
+        );
+    }
+}
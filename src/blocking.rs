@@ -0,0 +1,160 @@
+//! A client making blocking calls, for programs that aren't otherwise async.
+//!
+//! ```no_run
+//! use snapd::{api::find::FindRefreshable, blocking::BlockingSnapdClient};
+//!
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = BlockingSnapdClient::new()?;
+//!
+//! for snap in client.get(&FindRefreshable)?.info {
+//!     println!("{} can be refreshed to {}", snap.name, snap.version);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{future::Future, io};
+
+use hyper::StatusCode;
+use thiserror::Error;
+use tokio::runtime::{self, Handle, Runtime};
+
+use crate::{
+    api::{
+        assertions::GetAssertions,
+        changes::{Change, ChangeId},
+        snap::SnapName,
+        snaps::InstallOptions,
+        Envelope, Get, Post, Put,
+    },
+    SnapdClient, SnapdRequestError,
+};
+
+/// An error from a [`BlockingSnapdClient`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BlockingError {
+    /// The client was called from within an async runtime, where blocking would stall the
+    /// runtime's other tasks. Use the [`SnapdClient`] from async code instead.
+    #[error("the blocking snapd client can't be used from within an async runtime")]
+    InRuntime,
+    #[error(transparent)]
+    Request(#[from] SnapdRequestError),
+}
+
+/// A [`SnapdClient`] whose calls block until they're done, driving the client on a runtime of its
+/// own.
+///
+/// Its connections belong to that runtime, so it shouldn't share a [`SnapdPool`](crate::SnapdPool)
+/// with clients used from async code.
+pub struct BlockingSnapdClient {
+    client: SnapdClient,
+    /// Only taken when the client is dropped.
+    runtime: Option<Runtime>,
+}
+
+impl BlockingSnapdClient {
+    /// Creates a client for the system's `snapd`.
+    ///
+    /// Fails if its runtime can't be created.
+    pub fn new() -> io::Result<Self> {
+        Self::from_client(SnapdClient::new())
+    }
+
+    /// Creates a client making blocking calls through `client`.
+    ///
+    /// Fails if its runtime can't be created.
+    pub fn from_client(client: SnapdClient) -> io::Result<Self> {
+        let runtime = runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            client,
+            runtime: Some(runtime),
+        })
+    }
+
+    /// The async client calls are made through.
+    pub fn client(&self) -> &SnapdClient {
+        &self.client
+    }
+
+    /// Runs `future` to completion on the client's runtime, such as a call to one of the
+    /// [`client`](Self::client)'s conveniences without a blocking counterpart here.
+    pub fn block_on<F: Future>(&self, future: F) -> Result<F::Output, BlockingError> {
+        // Blocking inside a runtime would panic, rather than just stall it.
+        if Handle::try_current().is_ok() {
+            return Err(BlockingError::InRuntime);
+        }
+        let runtime = self
+            .runtime
+            .as_ref()
+            .expect("runtime is only taken on drop");
+        Ok(runtime.block_on(future))
+    }
+
+    /// Sends a GET request to `snapd`, like [`SnapdClient::get`].
+    pub fn get<G: Get>(&self, request: &G) -> Result<G::Payload, BlockingError> {
+        Ok(self.block_on(self.client.get(request))??)
+    }
+
+    /// Sends a HEAD request to `snapd`, like [`SnapdClient::head`].
+    pub fn head<G: Get>(&self, request: &G) -> Result<StatusCode, BlockingError> {
+        Ok(self.block_on(self.client.head(request))??)
+    }
+
+    /// Sends a GET request for assertions to `snapd`, like [`SnapdClient::get_assertions`].
+    pub fn get_assertions<G: GetAssertions>(
+        &self,
+        request: &G,
+    ) -> Result<G::Payload, BlockingError> {
+        Ok(self.block_on(self.client.get_assertions(request))??)
+    }
+
+    /// Sends a PUT request to `snapd`, like [`SnapdClient::put`].
+    pub fn put<P: Put>(&self, request: &P) -> Result<ChangeId, BlockingError> {
+        Ok(self.block_on(self.client.put(request))??)
+    }
+
+    /// Sends a POST request to `snapd`, like [`SnapdClient::post`].
+    pub fn post<P: Post>(
+        &self,
+        request: &P,
+    ) -> Result<<P::Response as Envelope>::Output, BlockingError> {
+        Ok(self.block_on(self.client.post(request))??)
+    }
+
+    /// Installs the snap `name` from the store, like [`SnapdClient::install`].
+    pub fn install(
+        &self,
+        name: SnapName<'_>,
+        options: InstallOptions<'_>,
+    ) -> Result<ChangeId, BlockingError> {
+        Ok(self.block_on(self.client.install(name, options))??)
+    }
+
+    /// Installs the snap `name` from the store and waits for it to be installed, like
+    /// [`SnapdClient::install_and_wait`].
+    pub fn install_and_wait(
+        &self,
+        name: SnapName<'_>,
+        options: InstallOptions<'_>,
+    ) -> Result<Change<'static>, BlockingError> {
+        Ok(self.block_on(self.client.install_and_wait(name, options))??)
+    }
+
+    /// Waits for the change `id` to be ready, like [`SnapdClient::wait_for_change`].
+    pub fn wait_for_change(&self, id: &ChangeId) -> Result<Change<'static>, BlockingError> {
+        Ok(self.block_on(self.client.wait_for_change(id))??)
+    }
+}
+
+impl Drop for BlockingSnapdClient {
+    fn drop(&mut self) {
+        // Dropping a runtime waits for its tasks, which panics if that would block another
+        // runtime, as when the client is dropped from async code. Nothing here needs waiting for.
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
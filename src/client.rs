@@ -0,0 +1,656 @@
+mod cache;
+mod challenge;
+mod hooks;
+mod keep_alive;
+mod pages;
+mod stream;
+mod tasks;
+
+use std::{
+    borrow::Cow, future::Future, net::SocketAddr, num::NonZeroUsize, sync::Arc, time::Duration,
+};
+
+use deadpool::{
+    managed::{BuildError, PoolError},
+    Status,
+};
+use hyper::{
+    body::Bytes,
+    header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE, HOST, WWW_AUTHENTICATE},
+    Method, Request, Response, StatusCode, Uri,
+};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+use url::{Position, Url};
+
+use crate::{
+    api::{
+        assertions::{AssertionParseError, GetAssertions},
+        changes::ChangeId,
+        AsyncResponse, Envelope, Get, GetStream, Paginated, Post, PostBytes, PostForm, Put,
+        SnapdError, SyncResponse,
+    },
+    connection::{
+        SnapdConnectionError, SnapdConnectionManager, SnapdPool, SnapdRequestBody, Transport,
+    },
+};
+use cache::LookupCache;
+pub use challenge::Challenge;
+use hooks::Hooks;
+pub use hooks::{RequestInfo, ResponseInfo};
+use keep_alive::KeepAlive;
+pub use pages::Pages;
+pub use stream::ResponseStream;
+pub(crate) use tasks::join_requests;
+
+/// An error sending a request to `snapd` or reading its response.
+///
+/// More variants may be added, so matches on it need a wildcard arm:
+///
+/// ```no_run
+/// # async fn f(client: snapd::SnapdClient) {
+/// use snapd::{api::system_info::GetSystemInfo, SnapdRequestError};
+///
+/// match client.get(&GetSystemInfo).await {
+///     Ok(info) => println!("snapd {}", info.version),
+///     Err(SnapdRequestError::Unauthorized { .. }) => println!("log in and try again"),
+///     Err(e) => println!("error: {e}"),
+/// }
+/// # }
+/// ```
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SnapdRequestError {
+    #[error("could not get a connection to snapd: {0}")]
+    Connection(#[from] PoolError<SnapdConnectionError>),
+    #[error("could not build request: {0}")]
+    Request(#[from] hyper::http::Error),
+    #[error("error communicating with snapd: {0}")]
+    Http(#[from] hyper::Error),
+    #[error("could not encode request body: {0}")]
+    Encode(#[source] serde_json::Error),
+    #[error("could not decode snapd's response: {0}")]
+    Decode(#[from] serde_json::Error),
+    #[error("could not parse snapd's assertions: {0}")]
+    Assertion(#[from] AssertionParseError),
+    #[error("snapd responded with {status}: {error}")]
+    Snapd {
+        status: StatusCode,
+        error: SnapdError,
+    },
+    /// The request needs authentication. Once logged in, it can be retried.
+    #[error("snapd requires authentication: {error}")]
+    Unauthorized {
+        /// How to authenticate, if `snapd` sent a `WWW-Authenticate` challenge.
+        challenge: Option<Box<Challenge>>,
+        error: SnapdError,
+    },
+    #[error("the request was cancelled")]
+    Cancelled,
+    #[error("snapd doesn't allow {0} requests to this endpoint")]
+    MethodNotAllowed(Method),
+    /// The response was longer than the client's
+    /// [maximum response size](SnapdClient::with_max_response_size).
+    #[error("snapd's response was larger than the limit of {limit} bytes")]
+    ResponseTooLarge { limit: usize },
+    /// The client's base URL, from its [`Transport`](crate::Transport), can't have a path, so no
+    /// request can be made relative to it.
+    #[error("cannot make requests relative to the base URL {0}")]
+    InvalidBaseUrl(Url),
+    /// Waiting for something took longer than the time allowed.
+    #[error("timed out after {0:?}")]
+    TimedOut(Duration),
+}
+
+/// An error creating a [`SnapdClient`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SnapdClientError {
+    #[error("could not make the connection pool for snapd: {0}")]
+    Pool(#[from] BuildError),
+}
+
+/// A client for `snapd`, holding a pool of connections to its socket.
+///
+/// Cloning a client is cheap, and clones share the same pool.
+#[derive(Clone)]
+pub struct SnapdClient {
+    pool: SnapdPool,
+    base_url: Url,
+    hooks: Hooks,
+    lookups: Option<Arc<LookupCache>>,
+    cancel: Option<CancellationToken>,
+    max_response_size: Option<usize>,
+    keep_alive: Option<Arc<KeepAlive>>,
+}
+
+impl SnapdClient {
+    /// Creates a client for the system's `snapd`.
+    ///
+    /// No connection is made until the first request is sent.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a client for the system's `snapd`, like [`new`](Self::new), but returns an error
+    /// rather than panicking if its connection pool can't be made.
+    pub fn try_new() -> Result<Self, SnapdClientError> {
+        Self::try_from_manager(SnapdConnectionManager::default())
+    }
+
+    /// Creates a client sending its requests through `pool`.
+    ///
+    /// This allows connecting to a socket other than [`SNAPD_SOCKET`](crate::SNAPD_SOCKET), or
+    /// tuning the pool:
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use snapd::{SnapdClient, SnapdConnectionManager, SnapdPool};
+    ///
+    /// let manager = SnapdConnectionManager::new("/tmp/snapd.socket")
+    ///     .with_connect_timeout(Duration::from_secs(5));
+    /// let pool = SnapdPool::builder(manager)
+    ///     .max_size(2)
+    ///     .build()
+    ///     .unwrap();
+    /// let client = SnapdClient::from_pool(pool);
+    /// ```
+    pub fn from_pool(pool: SnapdPool) -> Self {
+        Self {
+            base_url: pool.manager().base_url().clone(),
+            pool,
+            hooks: Hooks::default(),
+            lookups: None,
+            cancel: None,
+            max_response_size: None,
+            keep_alive: None,
+        }
+    }
+
+    /// Creates a client for a `snapd` exposed over plain TCP at `addr`.
+    pub fn over_tcp(addr: SocketAddr) -> Self {
+        Self::from_manager(SnapdConnectionManager::tcp(addr))
+    }
+
+    /// Creates a client for a `snapd` exposed over TCP at `addr`, behind TLS.
+    ///
+    /// `server_name` is the name the server's certificate is checked against.
+    #[cfg(feature = "tls")]
+    pub fn over_tls(
+        addr: SocketAddr,
+        server_name: crate::rustls::pki_types::ServerName<'static>,
+        config: std::sync::Arc<crate::rustls::ClientConfig>,
+    ) -> Self {
+        Self::from_manager(SnapdConnectionManager::tls(addr, server_name, config))
+    }
+
+    /// Creates a client opening its connections through `transport`.
+    pub fn with_transport(transport: impl Transport) -> Self {
+        Self::from_manager(SnapdConnectionManager::with_transport(transport))
+    }
+
+    fn from_manager(manager: SnapdConnectionManager) -> Self {
+        Self::try_from_manager(manager).expect("error making connection pool for snapd")
+    }
+
+    fn try_from_manager(manager: SnapdConnectionManager) -> Result<Self, SnapdClientError> {
+        Ok(Self::from_pool(SnapdPool::builder(manager).build()?))
+    }
+
+    /// Calls `hook` before each request is sent, e.g. to log it.
+    pub fn on_request(mut self, hook: impl Fn(&RequestInfo<'_>) + Send + Sync + 'static) -> Self {
+        self.hooks.on_request = Some(Arc::new(hook));
+        self
+    }
+
+    /// Calls `hook` after each response is received, e.g. to log it.
+    pub fn on_response(mut self, hook: impl Fn(&ResponseInfo<'_>) + Send + Sync + 'static) -> Self {
+        self.hooks.on_response = Some(Arc::new(hook));
+        self
+    }
+
+    /// Remembers up to `capacity` snap name and ID pairs, so that
+    /// [`SnapNameFromId`](crate::api::assertions::SnapNameFromId) and
+    /// [`SnapIdFromName`](crate::api::find::SnapIdFromName) don't ask `snapd` about the same snap
+    /// twice.
+    ///
+    /// Clones of the client made afterwards share the cache.
+    pub fn with_lookup_cache(mut self, capacity: NonZeroUsize) -> Self {
+        self.lookups = Some(Arc::new(LookupCache::new(capacity)));
+        self
+    }
+
+    /// The URL requests are made relative to.
+    pub(crate) fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    pub(crate) fn lookup_cache(&self) -> Option<&LookupCache> {
+        self.lookups.as_deref()
+    }
+
+    /// Fails every request made through this client with [`SnapdRequestError::Cancelled`] once
+    /// `token` is cancelled, including those already in flight and any
+    /// [`wait_for_change`](Self::wait_for_change) polling.
+    ///
+    /// Only this client, and clones made from it afterwards, are affected, so a request can be
+    /// made cancellable by sending it through a clone:
+    ///
+    /// ```no_run
+    /// # async fn f(client: snapd::SnapdClient, id: snapd::api::changes::ChangeId) {
+    /// use snapd::CancellationToken;
+    ///
+    /// let token = CancellationToken::new();
+    /// let waiting = client.clone().with_cancellation(token.clone());
+    /// // Calling `token.cancel()`, say when the user presses Escape, stops the wait.
+    /// let change = waiting.wait_for_change(&id).await;
+    /// # }
+    /// ```
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Fails any request whose response body is longer than `limit` bytes with
+    /// [`SnapdRequestError::ResponseTooLarge`], rather than buffering all of it.
+    ///
+    /// Responses are otherwise read whole, however long they are.
+    pub fn with_max_response_size(mut self, limit: usize) -> Self {
+        self.max_response_size = Some(limit);
+        self
+    }
+
+    /// Checks the pool's idle connections every `interval` in the background, with a cheap
+    /// request over each, closing those it fails on or that don't answer within `interval`.
+    /// Without this, a connection `snapd` dropped while it was idle is only found to be dead when
+    /// a request fails on it.
+    ///
+    /// The checks start with the first request sent, and stop once the pool is dropped.
+    pub fn with_keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(Arc::new(KeepAlive::new(interval)));
+        self
+    }
+
+    /// Runs `future` to completion, unless this client's cancellation token is cancelled first.
+    pub(crate) async fn cancellable<T>(
+        &self,
+        future: impl Future<Output = Result<T, SnapdRequestError>>,
+    ) -> Result<T, SnapdRequestError> {
+        match &self.cancel {
+            Some(token) => token
+                .run_until_cancelled(future)
+                .await
+                .unwrap_or(Err(SnapdRequestError::Cancelled)),
+            None => future.await,
+        }
+    }
+
+    /// The state of the client's connection pool, such as how many connections are open.
+    pub fn pool_status(&self) -> Status {
+        self.pool.status()
+    }
+
+    /// Opens connections until the pool holds `n` of them, so that the first requests made don't
+    /// wait to connect. `n` is capped at the pool's maximum size.
+    ///
+    /// Fails with the first connection that can't be opened, which the manager's
+    /// [connect timeout](SnapdConnectionManager::with_connect_timeout) bounds if it has one.
+    pub async fn warm_up(&self, n: usize) -> Result<(), SnapdRequestError> {
+        let n = n.min(self.pool.status().max_size);
+        self.cancellable(async {
+            // Holding on to each connection makes the pool open a new one for the next.
+            let mut connections = Vec::with_capacity(n);
+            for _ in 0..n {
+                connections.push(self.pool.get().await?);
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Sends a GET request to `snapd`, returning the decoded result.
+    pub async fn get<G: Get>(&self, request: &G) -> Result<G::Payload, SnapdRequestError> {
+        let response = self
+            .send(build_get_request(Method::GET, request, &self.base_url)?)
+            .await?;
+
+        parse_response(&response)
+    }
+
+    /// Fetches the results of `request` a page at a time, each picking up after the last, as a
+    /// [`Stream`](futures_core::Stream) of pages.
+    ///
+    /// ```no_run
+    /// # async fn f(client: snapd::SnapdClient) -> Result<(), snapd::SnapdRequestError> {
+    /// use snapd::api::notices::GetNotices;
+    ///
+    /// let mut pages = client.pages(GetNotices::default());
+    /// while let Some(notices) = pages.next_page().await? {
+    ///     for notice in notices {
+    ///         println!("{:?} {}", notice.notice_type, notice.key);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pages<P: Paginated>(&self, request: P) -> Pages<P>
+    where
+        P::Payload: Send + 'static,
+    {
+        Pages::new(self.clone(), request)
+    }
+
+    /// Sends a HEAD request to the endpoint of `request`, returning only the status `snapd`
+    /// responds with, without fetching the response body.
+    ///
+    /// `snapd` doesn't route HEAD requests to most endpoints, and responds with a
+    /// `405 Method Not Allowed`, which is returned as [`SnapdRequestError::MethodNotAllowed`].
+    pub async fn head<G: Get>(&self, request: &G) -> Result<StatusCode, SnapdRequestError> {
+        let response = self
+            .send(build_get_request(Method::HEAD, request, &self.base_url)?)
+            .await?;
+
+        match response.status() {
+            StatusCode::METHOD_NOT_ALLOWED => {
+                Err(SnapdRequestError::MethodNotAllowed(Method::HEAD))
+            }
+            status => Ok(status),
+        }
+    }
+
+    /// Sends a GET request for assertions to `snapd`, returning them parsed.
+    pub async fn get_assertions<G: GetAssertions>(
+        &self,
+        request: &G,
+    ) -> Result<G::Payload, SnapdRequestError> {
+        let body = self.get_bytes(&request.url(self.base_url.clone())).await?;
+        Ok(G::parse(&body)?)
+    }
+
+    /// Sends a GET request to `url`, returning the body of the response as is.
+    pub(crate) async fn get_bytes(&self, url: &Url) -> Result<Bytes, SnapdRequestError> {
+        let response = self
+            .send(build_request(Method::GET, url, SnapdRequestBody::Empty)?)
+            .await?;
+
+        check_status(&response)?;
+        Ok(response.into_body())
+    }
+
+    /// Sends a GET request to `snapd`, returning the body of its response to be read as it
+    /// arrives, rather than buffered whole.
+    ///
+    /// The body isn't subject to the client's
+    /// [maximum response size](Self::with_max_response_size), though an error response is.
+    pub async fn get_stream<G: GetStream>(
+        &self,
+        request: &G,
+    ) -> Result<ResponseStream, SnapdRequestError> {
+        let url = request.url(self.base_url.clone());
+        let request = build_request(Method::GET, &url, SnapdRequestBody::Empty)?;
+        self.before_send(&request);
+
+        let method = request.method().clone();
+        let uri = request.uri().clone();
+        let (connection, response) = self
+            .cancellable(async {
+                let mut connection = self.pool.get().await?;
+                let response = connection.send_request(request).await?;
+                Ok((connection, response))
+            })
+            .await?;
+
+        let (parts, body) = response.into_parts();
+        if parts.status.is_success() {
+            self.after_send(
+                &method,
+                &uri,
+                &Response::from_parts(parts.clone(), Bytes::new()),
+            );
+            let content_length = parts
+                .headers
+                .get(CONTENT_LENGTH)
+                .and_then(|length| length.to_str().ok()?.parse().ok());
+            return Ok(ResponseStream::new(
+                connection,
+                body,
+                content_length,
+                self.cancel.clone(),
+            ));
+        }
+
+        // An error is sent as JSON like any other, so it's read whole to be decoded.
+        let mut stream = ResponseStream::new(connection, body, None, self.cancel.clone());
+        let mut error = Vec::new();
+        while let Some(chunk) = stream.chunk().await? {
+            error.extend_from_slice(&chunk);
+            if let Some(limit) = self.max_response_size.filter(|&limit| error.len() > limit) {
+                return Err(SnapdRequestError::ResponseTooLarge { limit });
+            }
+        }
+        let response = Response::from_parts(parts, Bytes::from(error));
+        self.after_send(&method, &uri, &response);
+        Err(status_error(&response))
+    }
+
+    /// Sends a PUT request to `snapd`, returning the ID of the change it started.
+    pub async fn put<P: Put>(&self, request: &P) -> Result<ChangeId, SnapdRequestError> {
+        let url = request.url(self.base_url.clone());
+        let body = serde_json::to_vec(&request.body()).map_err(SnapdRequestError::Encode)?;
+        self.send_json::<AsyncResponse>(build_json_request(Method::PUT, &url, body)?)
+            .await
+    }
+
+    /// Sends a POST request to `snapd`, returning what it produced: the ID of the change it
+    /// started, or its result.
+    pub async fn post<P: Post>(
+        &self,
+        request: &P,
+    ) -> Result<<P::Response as Envelope>::Output, SnapdRequestError> {
+        let url = request.url(self.base_url.clone());
+        let body = serde_json::to_vec(&request.body()).map_err(SnapdRequestError::Encode)?;
+        self.send_json::<P::Response>(build_json_request(Method::POST, &url, body)?)
+            .await
+    }
+
+    /// Sends a POST request with a form to `snapd`, returning the ID of the change it started.
+    pub async fn post_form<P: PostForm>(&self, request: &P) -> Result<ChangeId, SnapdRequestError> {
+        let url = request.url(self.base_url.clone());
+        let (boundary, body) = encode_form(&request.fields());
+        let mut request = build_request(Method::POST, &url, SnapdRequestBody::Full(body.into()))?;
+        request.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_str(&format!("multipart/form-data; boundary={boundary}"))
+                .map_err(hyper::http::Error::from)?,
+        );
+        self.send_json::<AsyncResponse>(request).await
+    }
+
+    /// Sends a POST request with a raw body to `snapd`, returning what it produced.
+    pub async fn post_bytes<P: PostBytes>(
+        &self,
+        request: &P,
+    ) -> Result<<P::Response as Envelope>::Output, SnapdRequestError> {
+        let url = request.url(self.base_url.clone());
+        let body = Bytes::from(request.body().into_owned());
+        let mut request = build_request(Method::POST, &url, SnapdRequestBody::Full(body))?;
+        request
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(P::CONTENT_TYPE));
+        self.send_json::<P::Response>(request).await
+    }
+
+    /// Sends a request with a JSON body, decoding the response from the envelope `E`.
+    async fn send_json<E: Envelope>(
+        &self,
+        request: Request<SnapdRequestBody>,
+    ) -> Result<E::Output, SnapdRequestError> {
+        let response = self.send(request).await?;
+
+        check_status(&response)?;
+        Ok(serde_json::from_slice::<E>(response.body())?.into_output())
+    }
+
+    /// Sends `request` over a pooled connection, running any hooks around it.
+    async fn send(
+        &self,
+        request: Request<SnapdRequestBody>,
+    ) -> Result<Response<Bytes>, SnapdRequestError> {
+        self.before_send(&request);
+
+        let method = request.method().clone();
+        let uri = request.uri().clone();
+
+        let response = self
+            .cancellable(async {
+                self.pool
+                    .get()
+                    .await?
+                    .request_response(request, self.max_response_size)
+                    .await
+            })
+            .await?;
+
+        self.after_send(&method, &uri, &response);
+        Ok(response)
+    }
+
+    /// Starts any keep-alive checks, and runs the request hook on `request`.
+    fn before_send(&self, request: &Request<SnapdRequestBody>) {
+        if let Some(keep_alive) = &self.keep_alive {
+            keep_alive.start(self.pool.weak(), &self.base_url);
+        }
+
+        if let Some(hook) = &self.hooks.on_request {
+            hook(&RequestInfo {
+                method: request.method(),
+                uri: request.uri(),
+                body: request.body().as_bytes(),
+            });
+        }
+    }
+
+    /// Runs the response hook on `response`, the response to a `method` request to `uri`.
+    fn after_send(&self, method: &Method, uri: &Uri, response: &Response<Bytes>) {
+        if let Some(hook) = &self.hooks.on_response {
+            hook(&ResponseInfo {
+                method,
+                uri,
+                status: response.status(),
+                headers: response.headers(),
+                body: response.body(),
+            });
+        }
+    }
+}
+
+impl Default for SnapdClient {
+    fn default() -> Self {
+        Self::from_manager(SnapdConnectionManager::default())
+    }
+}
+
+fn build_request(
+    method: Method,
+    url: &Url,
+    body: SnapdRequestBody,
+) -> Result<Request<SnapdRequestBody>, SnapdRequestError> {
+    Ok(Request::builder()
+        .method(method)
+        .uri(&url[Position::BeforePath..])
+        .header(HOST, url.authority())
+        .body(body)?)
+}
+
+/// Builds a GET or HEAD request for the endpoint of `request`, with any headers it asks for.
+fn build_get_request<G: Get>(
+    method: Method,
+    request: &G,
+    base_url: &Url,
+) -> Result<Request<SnapdRequestBody>, SnapdRequestError> {
+    let url = request.url(base_url.clone());
+    let mut built = build_request(method, &url, SnapdRequestBody::Empty)?;
+    built.headers_mut().extend(request.headers());
+    Ok(built)
+}
+
+fn build_json_request(
+    method: Method,
+    url: &Url,
+    body: Vec<u8>,
+) -> Result<Request<SnapdRequestBody>, SnapdRequestError> {
+    let mut request = build_request(method, url, SnapdRequestBody::Full(body.into()))?;
+    request
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    Ok(request)
+}
+
+/// Encodes `fields` as a `multipart/form-data` body, returning the boundary between them along
+/// with the body.
+fn encode_form(fields: &[(&str, Cow<'_, [u8]>)]) -> (String, Vec<u8>) {
+    // The boundary can't appear in any of the values, which is checked rather than left to chance.
+    let mut boundary = String::from("snapd-rs-form-boundary");
+    while fields
+        .iter()
+        .any(|(_, value)| contains(value, boundary.as_bytes()))
+    {
+        boundary.push('-');
+    }
+
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+        body.extend_from_slice(
+            format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n").as_bytes(),
+        );
+        body.extend_from_slice(value);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    (boundary, body)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+/// Decodes snapd's response envelope, turning error responses into [`SnapdRequestError::Snapd`].
+fn parse_response<T: DeserializeOwned>(response: &Response<Bytes>) -> Result<T, SnapdRequestError> {
+    check_status(response)?;
+    Ok(serde_json::from_slice::<SyncResponse<T>>(response.body())?.result)
+}
+
+/// Turns an error response into [`SnapdRequestError::Snapd`], or
+/// [`SnapdRequestError::Unauthorized`] for a `401 Unauthorized`.
+fn check_status(response: &Response<Bytes>) -> Result<(), SnapdRequestError> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(status_error(response))
+    }
+}
+
+/// Decodes the error in an error response, like [`check_status`].
+fn status_error(response: &Response<Bytes>) -> SnapdRequestError {
+    let status = response.status();
+    let error = match serde_json::from_slice::<SyncResponse<SnapdError>>(response.body()) {
+        Ok(error) => error.result,
+        Err(e) => return e.into(),
+    };
+    if status == StatusCode::UNAUTHORIZED {
+        let challenge = response
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .and_then(|header| header.to_str().ok())
+            .and_then(Challenge::parse)
+            .map(Box::new);
+        SnapdRequestError::Unauthorized { challenge, error }
+    } else {
+        SnapdRequestError::Snapd { status, error }
+    }
+}
@@ -0,0 +1,47 @@
+//! Remembering which snap names and IDs go together.
+
+use std::{num::NonZeroUsize, sync::Mutex};
+
+use lru::LruCache;
+
+use crate::api::snap::{SnapId, SnapName};
+
+/// The most recently looked up snap names and IDs, in both directions.
+///
+/// A snap's name and ID almost never change, so entries are only ever evicted to make room.
+#[derive(Debug)]
+pub(crate) struct LookupCache {
+    names: Mutex<LruCache<String, SnapName<'static>>>,
+    ids: Mutex<LruCache<String, SnapId<'static>>>,
+}
+
+impl LookupCache {
+    pub(crate) fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            names: Mutex::new(LruCache::new(capacity)),
+            ids: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// The name of the snap with ID `id`, if it was looked up recently.
+    pub(crate) fn name(&self, id: &SnapId<'_>) -> Option<SnapName<'static>> {
+        self.names.lock().unwrap().get(id.as_str()).cloned()
+    }
+
+    /// The ID of the snap called `name`, if it was looked up recently.
+    pub(crate) fn id(&self, name: &SnapName<'_>) -> Option<SnapId<'static>> {
+        self.ids.lock().unwrap().get(name.as_str()).cloned()
+    }
+
+    /// Remembers that the snap with ID `id` is called `name`.
+    pub(crate) fn insert(&self, id: &SnapId<'static>, name: &SnapName<'static>) {
+        self.names
+            .lock()
+            .unwrap()
+            .put(id.as_str().to_owned(), name.clone());
+        self.ids
+            .lock()
+            .unwrap()
+            .put(name.as_str().to_owned(), id.clone());
+    }
+}
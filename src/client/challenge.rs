@@ -0,0 +1,148 @@
+//! The `WWW-Authenticate` challenges `snapd` sends along with a `401 Unauthorized`.
+
+use std::fmt;
+
+/// A challenge from a `WWW-Authenticate` header, saying how to authenticate, such as
+/// `Macaroon root="...", discharge="..."`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Challenge {
+    /// The authentication scheme, such as `Macaroon`.
+    pub scheme: String,
+    /// The scheme's parameters, in the order they were sent, with any quoting removed.
+    pub params: Vec<(String, String)>,
+}
+
+impl Challenge {
+    /// Parses a single challenge from the value of a `WWW-Authenticate` header.
+    ///
+    /// Returns `None` if there's no scheme. Parameters that aren't `name=value` pairs are skipped.
+    pub fn parse(header: &str) -> Option<Self> {
+        let header = header.trim();
+        let (scheme, mut rest) = header.split_once(' ').unwrap_or((header, ""));
+        if scheme.is_empty() {
+            return None;
+        }
+
+        let mut params = Vec::new();
+        loop {
+            rest = rest.trim_start_matches([' ', ',']);
+            let Some((name, after)) = rest.split_once('=') else {
+                break;
+            };
+
+            let (value, after) = match after.strip_prefix('"') {
+                Some(quoted) => unquote(quoted),
+                None => {
+                    let end = after.find(',').unwrap_or(after.len());
+                    (after[..end].trim().to_owned(), &after[end..])
+                }
+            };
+            params.push((name.trim().to_owned(), value));
+            rest = after;
+        }
+
+        Some(Self {
+            scheme: scheme.to_owned(),
+            params,
+        })
+    }
+
+    /// The value of the parameter `name`, if it was sent. Names are matched case-insensitively.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.scheme)?;
+        for (i, (name, value)) in self.params.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(f, "{separator}{name}={value:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a quoted string up to its closing quote, returning it unescaped along with what follows.
+fn unquote(quoted: &str) -> (String, &str) {
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return (value, &quoted[i + 1..]),
+            '\\' => value.extend(chars.next().map(|(_, c)| c)),
+            c => value.push(c),
+        }
+    }
+    // An unterminated string runs to the end of the header.
+    (value, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_values() {
+        let challenge =
+            Challenge::parse(r#"Macaroon root="AgEMbG9j", discharge="https://login.ubuntu.com/""#)
+                .unwrap();
+
+        assert_eq!(challenge.scheme, "Macaroon");
+        assert_eq!(
+            challenge.params,
+            [
+                ("root".to_owned(), "AgEMbG9j".to_owned()),
+                (
+                    "discharge".to_owned(),
+                    "https://login.ubuntu.com/".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(
+            challenge.param("DISCHARGE"),
+            Some("https://login.ubuntu.com/")
+        );
+    }
+
+    #[test]
+    fn escaped_quotes() {
+        let challenge =
+            Challenge::parse(r#"Basic realm="say \"hi\", \\ then", charset="UTF-8""#).unwrap();
+
+        assert_eq!(challenge.param("realm"), Some(r#"say "hi", \ then"#));
+        assert_eq!(challenge.param("charset"), Some("UTF-8"));
+    }
+
+    #[test]
+    fn unquoted_values() {
+        let challenge = Challenge::parse("Bearer error=invalid_token, scope = snaps").unwrap();
+
+        assert_eq!(challenge.param("error"), Some("invalid_token"));
+        assert_eq!(challenge.param("scope"), Some("snaps"));
+    }
+
+    #[test]
+    fn unterminated_quote_runs_to_the_end() {
+        let challenge = Challenge::parse(r#"Macaroon root="AgEM, discharge=x"#).unwrap();
+
+        assert_eq!(
+            challenge.params,
+            [("root".to_owned(), "AgEM, discharge=x".to_owned())]
+        );
+    }
+
+    #[test]
+    fn scheme_alone() {
+        let challenge = Challenge::parse(" Macaroon ").unwrap();
+
+        assert_eq!(challenge.scheme, "Macaroon");
+        assert!(challenge.params.is_empty());
+        assert_eq!(challenge.to_string(), "Macaroon");
+        assert_eq!(Challenge::parse("   "), None);
+    }
+}
@@ -0,0 +1,46 @@
+//! Callbacks for observing the traffic between a client and `snapd`.
+
+use std::{fmt, sync::Arc};
+
+use hyper::{HeaderMap, Method, StatusCode, Uri};
+
+/// A request about to be sent to `snapd`.
+#[derive(Debug)]
+pub struct RequestInfo<'a> {
+    pub method: &'a Method,
+    pub uri: &'a Uri,
+    pub body: &'a [u8],
+}
+
+/// A response received from `snapd`, along with the request it answers.
+#[derive(Debug)]
+pub struct ResponseInfo<'a> {
+    pub method: &'a Method,
+    pub uri: &'a Uri,
+    pub status: StatusCode,
+    /// The response's headers, such as the `WWW-Authenticate` challenge sent along with a
+    /// `401 Unauthorized`.
+    pub headers: &'a HeaderMap,
+    /// The response's body, which is empty for one returned as a
+    /// [`ResponseStream`](crate::ResponseStream), as it hasn't been read yet.
+    pub body: &'a [u8],
+}
+
+pub(crate) type RequestHook = Arc<dyn Fn(&RequestInfo<'_>) + Send + Sync>;
+pub(crate) type ResponseHook = Arc<dyn Fn(&ResponseInfo<'_>) + Send + Sync>;
+
+/// The hooks registered on a client.
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) on_request: Option<RequestHook>,
+    pub(crate) on_response: Option<ResponseHook>,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("on_request", &self.on_request.is_some())
+            .field("on_response", &self.on_response.is_some())
+            .finish()
+    }
+}
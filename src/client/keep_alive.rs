@@ -0,0 +1,88 @@
+//! Checking idle pooled connections in the background, so dead ones are closed before a request
+//! is sent over them.
+
+use std::{sync::Once, time::Duration};
+
+use deadpool::managed::{Object, Timeouts, WeakPool};
+use hyper::Method;
+use url::Url;
+
+use super::build_request;
+use crate::connection::{SnapdConnectionManager, SnapdRequestBody};
+
+/// How often to check the idle connections of a client's pool, and whether the task doing so has
+/// been started.
+#[derive(Debug)]
+pub(crate) struct KeepAlive {
+    interval: Duration,
+    started: Once,
+}
+
+impl KeepAlive {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            started: Once::new(),
+        }
+    }
+
+    /// Starts checking the idle connections of `pool`, unless that's already been started.
+    ///
+    /// This must be called from within a Tokio runtime. The checks stop once the pool is dropped.
+    pub(crate) fn start(&self, pool: WeakPool<SnapdConnectionManager>, base_url: &Url) {
+        self.started.call_once(|| {
+            let mut url = base_url.clone();
+            url.set_path("/v2/system-info");
+            tokio::spawn(check_idle(pool, url, self.interval));
+        });
+    }
+}
+
+/// Every `interval`, sends a request to `url` over each idle connection in `pool`, and closes
+/// those it fails on or that don't answer in time.
+async fn check_idle(pool: WeakPool<SnapdConnectionManager>, url: Url, interval: Duration) {
+    let mut ticks = tokio::time::interval(interval);
+    // The first tick completes straight away, when every connection was just used.
+    ticks.tick().await;
+
+    loop {
+        ticks.tick().await;
+        let Some(pool) = pool.upgrade() else {
+            return;
+        };
+
+        // Those already known to be dead are closed first, as recycling them on the way out of
+        // the pool would open new connections in their place.
+        pool.retain(|connection, _| !connection.is_closed());
+
+        // Taking every idle connection out at once keeps any from being checked twice.
+        // Connections in use aren't waited for.
+        let timeouts = Timeouts {
+            wait: Some(Duration::ZERO),
+            create: None,
+            ..pool.timeouts()
+        };
+        let mut idle = Vec::new();
+        for _ in 0..pool.status().available {
+            match pool.timeout_get(&timeouts).await {
+                // One that's never been recycled was only just opened, as there were no more idle
+                // ones to take, so it's put straight back.
+                Ok(connection) if Object::metrics(&connection).recycle_count == 0 => break,
+                Ok(connection) => idle.push(connection),
+                Err(_) => break,
+            }
+        }
+
+        for mut connection in idle {
+            let Ok(request) = build_request(Method::GET, &url, SnapdRequestBody::Empty) else {
+                return;
+            };
+            // A connection that's half open may never answer, so it's given until the next check.
+            let response =
+                tokio::time::timeout(interval, connection.request_response(request, None));
+            if !matches!(response.await, Ok(Ok(_))) {
+                drop(Object::take(connection));
+            }
+        }
+    }
+}
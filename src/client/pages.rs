@@ -0,0 +1,117 @@
+//! Fetching the results of a paginated endpoint a page at a time.
+
+use std::{
+    future::{self, Future},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+use hyper::Method;
+
+use super::{build_get_request, parse_response};
+use crate::{api::Paginated, SnapdClient, SnapdRequestError};
+
+/// A page being fetched, which holds nothing borrowed from the request, so the request can be
+/// moved on once the page arrives.
+type Fetch<T> = Pin<Box<dyn Future<Output = Result<T, SnapdRequestError>> + Send>>;
+
+/// The pages of a [`Paginated`] request, as returned by [`SnapdClient::pages`].
+///
+/// This is a [`Stream`] of pages, which ends once an empty page is fetched. A page that fails to
+/// be fetched is yielded as an error, and polling again fetches the same page, so the request can
+/// be retried. [`next_page`](Self::next_page) gets the next page without needing a `Stream`
+/// extension trait.
+pub struct Pages<P: Paginated> {
+    client: SnapdClient,
+    request: P,
+    /// The page being fetched, if one is.
+    fetching: Option<Fetch<P::Payload>>,
+    done: bool,
+}
+
+impl<P: Paginated> Pages<P> {
+    pub(crate) fn new(client: SnapdClient, request: P) -> Self {
+        Self {
+            client,
+            request,
+            fetching: None,
+            done: false,
+        }
+    }
+
+    /// The request for the next page.
+    pub fn request(&self) -> &P {
+        &self.request
+    }
+}
+
+impl<P: Paginated> Pages<P>
+where
+    P::Payload: Send + 'static,
+{
+    /// Fetches the next page, or returns `None` once an empty page ends them.
+    ///
+    /// A request that fails can be retried by calling this again, which fetches the same page.
+    pub async fn next_page(&mut self) -> Result<Option<P::Payload>, SnapdRequestError> {
+        future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx))
+            .await
+            .transpose()
+    }
+
+    fn fetch(&self) -> Fetch<P::Payload> {
+        let client = self.client.clone();
+        let request = build_get_request(Method::GET, &self.request, &client.base_url);
+        Box::pin(async move {
+            let response = client.send(request?).await?;
+            parse_response(&response)
+        })
+    }
+}
+
+// Nothing is pinned structurally: the page being fetched is boxed, and the request is only ever
+// reached through `&mut`.
+impl<P: Paginated> Unpin for Pages<P> {}
+
+impl<P: Paginated> Stream for Pages<P>
+where
+    P::Payload: Send + 'static,
+{
+    type Item = Result<P::Payload, SnapdRequestError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let fetching = match &mut this.fetching {
+            Some(fetching) => fetching,
+            None => this.fetching.insert(this.fetch()),
+        };
+        let page = ready!(fetching.as_mut().poll(cx));
+        this.fetching = None;
+
+        Poll::Ready(match page {
+            Ok(page) if this.request.advance(&page) => Some(Ok(page)),
+            Ok(_) => {
+                this.done = true;
+                None
+            }
+            Err(e) => Some(Err(e)),
+        })
+    }
+}
+
+impl<P: Paginated + Clone> Clone for Pages<P> {
+    /// Clones the pages from the request for the next page. A page being fetched isn't shared,
+    /// so the clone fetches it again.
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            request: self.request.clone(),
+            fetching: None,
+            done: self.done,
+        }
+    }
+}
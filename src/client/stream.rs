@@ -0,0 +1,93 @@
+//! Responses whose bodies are read as they arrive, rather than buffered whole.
+
+use std::fmt;
+
+use deadpool::managed::Object;
+use http_body_util::BodyExt;
+use hyper::body::{Bytes, Incoming};
+use tokio_util::sync::CancellationToken;
+
+use crate::{connection::SnapdConnectionManager, SnapdRequestError};
+
+/// The body of a response from `snapd`, such as an
+/// [`ExportSnapshot`](crate::api::snapshots::ExportSnapshot), read a chunk at a time.
+///
+/// The connection it arrives over is held until the body has been read to the end, and is closed
+/// rather than returned to the pool if the stream is dropped before then.
+pub struct ResponseStream {
+    /// The connection the body arrives over, until the body has been read to the end.
+    connection: Option<Object<SnapdConnectionManager>>,
+    body: Incoming,
+    content_length: Option<u64>,
+    cancel: Option<CancellationToken>,
+}
+
+impl ResponseStream {
+    pub(crate) fn new(
+        connection: Object<SnapdConnectionManager>,
+        body: Incoming,
+        content_length: Option<u64>,
+        cancel: Option<CancellationToken>,
+    ) -> Self {
+        Self {
+            connection: Some(connection),
+            body,
+            content_length,
+            cancel,
+        }
+    }
+
+    /// The length of the whole body, if `snapd` said what it is.
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Reads the next chunk of the body, or returns `None` once all of it has been read.
+    ///
+    /// Fails with [`SnapdRequestError::Cancelled`] if the client the stream came from is
+    /// [cancelled](crate::SnapdClient::with_cancellation).
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, SnapdRequestError> {
+        match self.cancel.clone() {
+            Some(token) => token
+                .run_until_cancelled(self.next_data())
+                .await
+                .unwrap_or(Err(SnapdRequestError::Cancelled)),
+            None => self.next_data().await,
+        }
+    }
+
+    async fn next_data(&mut self) -> Result<Option<Bytes>, SnapdRequestError> {
+        loop {
+            let Some(frame) = self.body.frame().await else {
+                // The whole response has been read, so the connection can go back to the pool.
+                if let Some(mut connection) = self.connection.take() {
+                    connection.finish_response();
+                }
+                return Ok(None);
+            };
+            // Trailers carry nothing of the body, so they're skipped, as are empty chunks.
+            match frame?.into_data() {
+                Ok(data) if !data.is_empty() => return Ok(Some(data)),
+                _ => continue,
+            }
+        }
+    }
+}
+
+impl Drop for ResponseStream {
+    fn drop(&mut self) {
+        // A half-read response leaves the connection unusable, so rather than returning it to the
+        // pool to be thrown away the next time it's taken, it's closed and its slot freed now.
+        if let Some(connection) = self.connection.take() {
+            drop(Object::take(connection));
+        }
+    }
+}
+
+impl fmt::Debug for ResponseStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseStream")
+            .field("content_length", &self.content_length)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,41 @@
+//! Sending several requests at once, each from its own task.
+
+use std::{future::Future, panic};
+
+use tokio::task::JoinSet;
+
+use crate::SnapdRequestError;
+
+/// Runs each of `requests` on its own task, returning each one's result alongside its key, in the
+/// order they were given.
+///
+/// A request whose task is cancelled, which only happens when the runtime it's running on shuts
+/// down, fails with [`SnapdRequestError::Cancelled`]. A task that panics has its panic resumed
+/// here.
+pub(crate) async fn join_requests<K, T, F>(
+    requests: impl IntoIterator<Item = (K, F)>,
+) -> Vec<(K, Result<T, SnapdRequestError>)>
+where
+    F: Future<Output = Result<T, SnapdRequestError>> + Send + 'static,
+    T: Send + 'static,
+{
+    let mut tasks = JoinSet::new();
+    let mut keys = Vec::new();
+    for (index, (key, request)) in requests.into_iter().enumerate() {
+        tasks.spawn(async move { (index, request.await) });
+        keys.push(key);
+    }
+
+    let mut results: Vec<_> = keys.iter().map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, result)) => results[index] = Some(result),
+            Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+            Err(_) => {}
+        }
+    }
+    keys.into_iter()
+        .zip(results)
+        .map(|(key, result)| (key, result.unwrap_or(Err(SnapdRequestError::Cancelled))))
+        .collect()
+}
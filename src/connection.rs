@@ -0,0 +1,336 @@
+//! Pooled HTTP/1 connections to `snapd`, over its Unix socket or TCP.
+
+mod body;
+mod io;
+mod transport;
+
+use std::{fmt, io::ErrorKind, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use deadpool::managed::{Manager, Metrics, Pool, RecycleError, RecycleResult};
+use http_body_util::{BodyExt, Limited};
+use hyper::{
+    body::{Bytes, Incoming},
+    client::conn::http1::{self, SendRequest},
+    header::CONTENT_LENGTH,
+    Request, Response,
+};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    task::JoinHandle,
+};
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::{pki_types::ServerName, ClientConfig};
+use url::Url;
+
+use crate::SnapdRequestError;
+
+pub(crate) use body::SnapdRequestBody;
+use io::SocketIo;
+use transport::DynTransport;
+#[cfg(feature = "tls")]
+pub use transport::TlsTransport;
+pub use transport::{TcpTransport, Transport, UnixTransport};
+
+/// Where `snapd` listens for API requests.
+pub const SNAPD_SOCKET: &str = "/run/snapd.socket";
+
+/// Where `snapd` listens for requests from within snaps, which may only use a few endpoints, such
+/// as [`SnapCtl`](crate::api::snapctl::SnapCtl).
+pub const SNAPD_SNAP_SOCKET: &str = "/run/snapd-snap.socket";
+
+/// A pool of connections to `snapd`, which a [`SnapdClient`](crate::SnapdClient) can be built from.
+pub type SnapdPool = Pool<SnapdConnectionManager>;
+
+/// An error establishing a connection to `snapd`.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SnapdConnectionError {
+    /// The socket doesn't exist, as is the case where `snapd` isn't installed, such as in most
+    /// containers.
+    #[error("could not connect to snapd, which may not be installed: {0}")]
+    SnapdNotInstalled(#[source] std::io::Error),
+    /// The socket can't be opened by this user. Most of the API is open to everyone, but some
+    /// sockets, such as [`SNAPD_SNAP_SOCKET`], are only open to snaps or root.
+    #[error("not allowed to connect to snapd, try running as root: {0}")]
+    PermissionDenied(#[source] std::io::Error),
+    /// Nothing is listening on the socket or address, such as while `snapd` is stopped or
+    /// restarting.
+    #[error("could not connect to snapd, which may not be running: {0}")]
+    SnapdNotRunning(#[source] std::io::Error),
+    /// The transport couldn't connect for some other reason.
+    #[error("could not connect to snapd: {0}")]
+    Connect(#[source] std::io::Error),
+    /// The stream opened, but HTTP couldn't be spoken over it.
+    #[error("HTTP handshake with snapd failed: {0}")]
+    Handshake(#[source] hyper::Error),
+    /// Opening the connection took longer than the
+    /// [connect timeout](SnapdConnectionManager::with_connect_timeout).
+    #[error("timed out connecting to snapd after {0:?}")]
+    Timeout(Duration),
+}
+
+impl From<std::io::Error> for SnapdConnectionError {
+    /// Classifies an error connecting by why it happened, so it can be reported helpfully.
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            ErrorKind::NotFound => Self::SnapdNotInstalled(e),
+            ErrorKind::PermissionDenied => Self::PermissionDenied(e),
+            ErrorKind::ConnectionRefused => Self::SnapdNotRunning(e),
+            _ => Self::Connect(e),
+        }
+    }
+}
+
+/// A single HTTP/1 connection to `snapd`.
+///
+/// The connection itself is driven by a background task, which finishes once `snapd` hangs up or
+/// the [`SendRequest`] half is dropped, and is aborted if the connection is dropped first.
+pub struct SnapdConnection {
+    sender: SendRequest<SnapdRequestBody>,
+    connection: JoinHandle<()>,
+    /// Set while a request is in flight. If it's still set when the connection is recycled, the
+    /// request was abandoned partway, possibly leaving a response half-read, so the connection
+    /// can't be reused.
+    in_flight: bool,
+}
+
+impl SnapdConnection {
+    /// Speaks HTTP/1 over an already-connected `stream`.
+    async fn handshake<S>(stream: S) -> Result<Self, SnapdConnectionError>
+    where
+        S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        let (sender, connection) = http1::handshake(SocketIo::new(stream))
+            .await
+            .map_err(SnapdConnectionError::Handshake)?;
+
+        // Any error on the connection is reported to whoever is waiting on `sender`, so there's
+        // nothing useful to do with it here.
+        let connection = tokio::spawn(async move {
+            let _ = connection.await;
+        });
+
+        Ok(Self {
+            sender,
+            connection,
+            in_flight: false,
+        })
+    }
+
+    /// Sends `request` and buffers the entire response body, keeping the status and headers.
+    ///
+    /// Fails with [`SnapdRequestError::ResponseTooLarge`] if the body is longer than `limit`,
+    /// without reading more of it than that. The connection is then left unusable.
+    pub(crate) async fn request_response(
+        &mut self,
+        request: Request<SnapdRequestBody>,
+        limit: Option<usize>,
+    ) -> Result<Response<Bytes>, SnapdRequestError> {
+        let (parts, body) = self.send_request(request).await?.into_parts();
+        let body = match limit {
+            Some(limit) => {
+                let too_large = SnapdRequestError::ResponseTooLarge { limit };
+                let length = parts.headers.get(CONTENT_LENGTH);
+                if length.and_then(|length| length.to_str().ok()?.parse::<u64>().ok())
+                    > Some(limit as u64)
+                {
+                    return Err(too_large);
+                }
+                match Limited::new(body, limit).collect().await {
+                    Ok(body) => body.to_bytes(),
+                    // The only other error is one reading the body.
+                    Err(e) => match e.downcast::<hyper::Error>() {
+                        Ok(e) => return Err((*e).into()),
+                        Err(_) => return Err(too_large),
+                    },
+                }
+            }
+            None => body.collect().await?.to_bytes(),
+        };
+        self.finish_response();
+
+        Ok(Response::from_parts(parts, body))
+    }
+
+    /// Sends `request`, returning the response before its body has been read.
+    ///
+    /// The connection can't be reused until the body has been read to the end and
+    /// [`finish_response`](Self::finish_response) called.
+    pub(crate) async fn send_request(
+        &mut self,
+        request: Request<SnapdRequestBody>,
+    ) -> Result<Response<Incoming>, SnapdRequestError> {
+        self.sender.ready().await?;
+
+        self.in_flight = true;
+        Ok(self.sender.send_request(request).await?)
+    }
+
+    /// Marks the response to the last request as read, so the connection can be reused.
+    pub(crate) fn finish_response(&mut self) {
+        self.in_flight = false;
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.sender.is_closed() || self.connection.is_finished()
+    }
+}
+
+impl Drop for SnapdConnection {
+    fn drop(&mut self) {
+        self.connection.abort();
+    }
+}
+
+/// Opens [`SnapdConnection`]s for a [`SnapdPool`], over some [`Transport`].
+///
+/// By default, connections are made to [`SNAPD_SOCKET`].
+#[derive(Clone)]
+pub struct SnapdConnectionManager {
+    transport: Arc<dyn DynTransport>,
+    base_url: Url,
+    connect_timeout: Option<Duration>,
+}
+
+impl SnapdConnectionManager {
+    /// Creates a manager connecting to the socket at `socket_path`.
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        Self::with_transport(UnixTransport::new(socket_path))
+    }
+
+    /// Creates a manager connecting to a socket in Linux's abstract namespace, such as `@snapd`.
+    ///
+    /// The leading `@` conventionally used to mark abstract names is optional.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn abstract_namespace(name: &str) -> Self {
+        Self::with_transport(UnixTransport::abstract_namespace(name))
+    }
+
+    /// Creates a manager connecting to a `snapd` exposed over plain TCP at `addr`.
+    pub fn tcp(addr: SocketAddr) -> Self {
+        Self::with_transport(TcpTransport::new(addr))
+    }
+
+    /// Creates a manager connecting to a `snapd` exposed over TCP at `addr`, behind TLS.
+    ///
+    /// `server_name` is the name the server's certificate is checked against.
+    #[cfg(feature = "tls")]
+    pub fn tls(
+        addr: SocketAddr,
+        server_name: ServerName<'static>,
+        config: Arc<ClientConfig>,
+    ) -> Self {
+        Self::with_transport(TlsTransport::new(addr, server_name, config))
+    }
+
+    /// Creates a manager opening its connections through `transport`.
+    pub fn with_transport(transport: impl Transport) -> Self {
+        Self {
+            base_url: transport.base_url(),
+            transport: Arc::new(transport),
+            connect_timeout: None,
+        }
+    }
+
+    /// Gives up on opening a connection after `timeout`, covering the transport's
+    /// [`connect`](Transport::connect), such as a TLS handshake, and the HTTP handshake.
+    ///
+    /// Without this, a `snapd` that's stuck and not accepting connections blocks the request
+    /// waiting on it indefinitely. Requests fail with [`SnapdConnectionError::Timeout`] instead.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// The URL requests through this manager's connections are relative to.
+    pub(crate) fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+}
+
+impl fmt::Debug for SnapdConnectionManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapdConnectionManager")
+            .field("base_url", &self.base_url.as_str())
+            .field("connect_timeout", &self.connect_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for SnapdConnectionManager {
+    fn default() -> Self {
+        Self::new(SNAPD_SOCKET)
+    }
+}
+
+impl Manager for SnapdConnectionManager {
+    type Type = SnapdConnection;
+    type Error = SnapdConnectionError;
+
+    async fn create(&self) -> Result<SnapdConnection, SnapdConnectionError> {
+        match self.connect_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.transport.open())
+                .await
+                .map_err(|_| SnapdConnectionError::Timeout(timeout))?,
+            None => self.transport.open().await,
+        }
+    }
+
+    async fn recycle(
+        &self,
+        connection: &mut SnapdConnection,
+        _: &Metrics,
+    ) -> RecycleResult<SnapdConnectionError> {
+        if connection.is_closed() {
+            Err(RecycleError::message("connection closed by snapd"))
+        } else if connection.in_flight {
+            Err(RecycleError::message("request was abandoned partway"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    fn classify(kind: ErrorKind) -> SnapdConnectionError {
+        io::Error::from(kind).into()
+    }
+
+    #[test]
+    fn a_missing_socket_means_snapd_isnt_installed() {
+        assert!(matches!(
+            classify(ErrorKind::NotFound),
+            SnapdConnectionError::SnapdNotInstalled(e) if e.kind() == ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn a_forbidden_socket_is_permission_denied() {
+        assert!(matches!(
+            classify(ErrorKind::PermissionDenied),
+            SnapdConnectionError::PermissionDenied(e) if e.kind() == ErrorKind::PermissionDenied
+        ));
+    }
+
+    #[test]
+    fn a_refused_connection_means_snapd_isnt_running() {
+        assert!(matches!(
+            classify(ErrorKind::ConnectionRefused),
+            SnapdConnectionError::SnapdNotRunning(e) if e.kind() == ErrorKind::ConnectionRefused
+        ));
+    }
+
+    #[test]
+    fn other_errors_are_left_unclassified() {
+        assert!(matches!(
+            classify(ErrorKind::ConnectionReset),
+            SnapdConnectionError::Connect(_)
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+use std::{
+    convert::Infallible,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use hyper::body::{Body, Bytes, Frame, SizeHint};
+
+/// The body of a request to `snapd`.
+#[derive(Debug, Default)]
+pub(crate) enum SnapdRequestBody {
+    #[default]
+    Empty,
+    /// A body sent in a single frame, such as JSON.
+    Full(Bytes),
+}
+
+impl SnapdRequestBody {
+    /// The bytes this body will send.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Empty => &[],
+            Self::Full(bytes) => bytes,
+        }
+    }
+}
+
+impl Body for SnapdRequestBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        match &mut *self {
+            Self::Empty => Poll::Ready(None),
+            Self::Full(bytes) if bytes.is_empty() => Poll::Ready(None),
+            Self::Full(bytes) => Poll::Ready(Some(Ok(Frame::data(std::mem::take(bytes))))),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self {
+            Self::Empty => true,
+            Self::Full(bytes) => bytes.is_empty(),
+        }
+    }
+
+    // hyper only sends a `Content-Length` when the size is exact, falling back to chunked encoding
+    // otherwise, so every variant reports its length up front.
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            Self::Empty => SizeHint::with_exact(0),
+            Self::Full(bytes) => SizeHint::with_exact(bytes.len() as u64),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! The ways a connection to `snapd` can be opened.
+
+#[cfg(feature = "tls")]
+use std::{fmt, sync::Arc};
+use std::{future::Future, io, net::SocketAddr, path::PathBuf, pin::Pin};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpStream, UnixStream},
+};
+#[cfg(feature = "tls")]
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{pki_types::ServerName, ClientConfig},
+    TlsConnector,
+};
+use url::Url;
+
+use super::{SnapdConnection, SnapdConnectionError};
+
+/// Opens streams that `snapd` can be spoken to over.
+///
+/// A [`SnapdConnectionManager`](super::SnapdConnectionManager) speaks HTTP/1 over whatever
+/// stream its transport opens, so the same request machinery is shared between the Unix socket,
+/// TCP, and anything else that can carry bytes.
+pub trait Transport: Send + Sync + 'static {
+    type Stream: AsyncRead + AsyncWrite + Send + Unpin + 'static;
+
+    /// Opens a new stream to `snapd`.
+    fn connect(&self) -> impl Future<Output = io::Result<Self::Stream>> + Send;
+
+    /// The URL requests over this transport are relative to. Its authority is sent as the `Host`
+    /// of every request.
+    fn base_url(&self) -> Url {
+        Url::parse("http://localhost").expect("base URL is valid")
+    }
+}
+
+/// Connects to `snapd`'s Unix socket.
+#[derive(Clone, Debug)]
+pub struct UnixTransport {
+    socket_path: PathBuf,
+}
+
+impl UnixTransport {
+    /// Connects to the socket at `socket_path`.
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+        }
+    }
+
+    /// Connects to a socket in Linux's abstract namespace, such as `@snapd`.
+    ///
+    /// The leading `@` conventionally used to mark abstract names is optional.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn abstract_namespace(name: &str) -> Self {
+        // tokio treats a path starting with a NUL byte as an abstract name.
+        Self::new(format!("\0{}", name.strip_prefix('@').unwrap_or(name)))
+    }
+}
+
+impl Transport for UnixTransport {
+    type Stream = UnixStream;
+
+    async fn connect(&self) -> io::Result<UnixStream> {
+        UnixStream::connect(&self.socket_path).await
+    }
+}
+
+/// Connects to a `snapd` exposed over plain TCP.
+#[derive(Clone, Debug)]
+pub struct TcpTransport {
+    addr: SocketAddr,
+}
+
+impl TcpTransport {
+    /// Connects to `snapd` listening at `addr`.
+    pub fn new(addr: SocketAddr) -> Self {
+        Self { addr }
+    }
+}
+
+impl Transport for TcpTransport {
+    type Stream = TcpStream;
+
+    async fn connect(&self) -> io::Result<TcpStream> {
+        TcpStream::connect(self.addr).await
+    }
+
+    fn base_url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.addr)).expect("socket address is a valid host")
+    }
+}
+
+/// Connects to a `snapd` exposed over TCP, behind TLS.
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+pub struct TlsTransport {
+    addr: SocketAddr,
+    server_name: ServerName<'static>,
+    connector: TlsConnector,
+}
+
+#[cfg(feature = "tls")]
+impl TlsTransport {
+    /// Connects to `addr`, checking the server's certificate against `server_name`.
+    pub fn new(
+        addr: SocketAddr,
+        server_name: ServerName<'static>,
+        config: Arc<ClientConfig>,
+    ) -> Self {
+        Self {
+            addr,
+            server_name,
+            connector: TlsConnector::from(config),
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+impl fmt::Debug for TlsTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsTransport")
+            .field("addr", &self.addr)
+            .field("server_name", &self.server_name)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "tls")]
+impl Transport for TlsTransport {
+    type Stream = TlsStream<TcpStream>;
+
+    async fn connect(&self) -> io::Result<TlsStream<TcpStream>> {
+        let stream = TcpStream::connect(self.addr).await?;
+        self.connector
+            .connect(self.server_name.clone(), stream)
+            .await
+    }
+
+    fn base_url(&self) -> Url {
+        Url::parse(&format!("https://{}", self.addr)).expect("socket address is a valid host")
+    }
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A [`Transport`] with its stream type erased, so managers don't need to be generic over it.
+pub(super) trait DynTransport: Send + Sync {
+    fn open(&self) -> BoxFuture<'_, Result<SnapdConnection, SnapdConnectionError>>;
+}
+
+impl<T: Transport> DynTransport for T {
+    fn open(&self) -> BoxFuture<'_, Result<SnapdConnection, SnapdConnectionError>> {
+        Box::pin(async move { SnapdConnection::handshake(self.connect().await?).await })
+    }
+}
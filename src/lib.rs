@@ -1 +1,39 @@
+//! A client for the `snapd` REST API, spoken over its Unix domain socket.
+//!
+//! Each of `snapd`'s endpoints is modelled as a request type in [`api`], which knows its own URL
+//! and the type of its response. Requests are sent through a [`SnapdClient`], which keeps a pool
+//! of connections to the socket open:
+//!
+//! ```no_run
+//! use snapd::{api::find::FindRefreshable, SnapdClient};
+//!
+//! # async fn run() -> Result<(), snapd::SnapdRequestError> {
+//! let client = SnapdClient::new();
+//!
+//! for snap in client.get(&FindRefreshable).await?.info {
+//!     println!("{} can be refreshed to {}", snap.name, snap.version);
+//! }
+//! # Ok(())
+//! # }
+//! ```
 
+pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod client;
+mod connection;
+
+pub use client::{
+    Challenge, Pages, RequestInfo, ResponseInfo, ResponseStream, SnapdClient, SnapdClientError,
+    SnapdRequestError,
+};
+#[cfg(feature = "tls")]
+pub use tokio_rustls::rustls;
+pub use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "tls")]
+pub use connection::TlsTransport;
+pub use connection::{
+    SnapdConnection, SnapdConnectionError, SnapdConnectionManager, SnapdPool, TcpTransport,
+    Transport, UnixTransport, SNAPD_SNAP_SOCKET, SNAPD_SOCKET,
+};
@@ -0,0 +1,78 @@
+//! Creating, removing and preferring aliases.
+
+mod common;
+
+use common::{async_change, error, reply, FakeSnapd};
+use hyper::Method;
+use serde_json::json;
+use snapd::api::{
+    aliases::{AliasChange, AliasError},
+    snap::SnapCommand,
+};
+
+#[tokio::test]
+async fn prefer_aliases_posts_prefer() {
+    let snapd = FakeSnapd::new(|_| async_change("9"));
+
+    let id = snapd.client().prefer_aliases("hello".into()).await.unwrap();
+
+    assert_eq!(id.as_str(), "9");
+    let request = &snapd.received()[0];
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.uri, "/v2/aliases");
+    assert_eq!(request.json(), json!({"action": "prefer", "snap": "hello"}));
+}
+
+#[tokio::test]
+async fn invalid_aliases_are_rejected_before_asking_snapd() {
+    let snapd = FakeSnapd::new(|_| async_change("9"));
+    let client = snapd.client();
+
+    assert!(matches!(
+        client
+            .alias(SnapCommand::parse("hello"), "not an alias")
+            .await,
+        Err(AliasError::InvalidAlias(_))
+    ));
+    assert!(matches!(
+        client.unalias("-hi").await,
+        Err(AliasError::InvalidAlias(_))
+    ));
+    assert!(snapd.received().is_empty());
+
+    client
+        .alias(SnapCommand::parse("hello"), "hi")
+        .await
+        .unwrap();
+    assert_eq!(
+        snapd.received()[0].json(),
+        json!({"action": "alias", "snap": "hello", "app": "hello", "alias": "hi"})
+    );
+}
+
+#[tokio::test]
+async fn unalias_and_wait_tells_disabled_aliases_from_removed_ones() {
+    let snapd = FakeSnapd::new(|request| match (&request.method, request.path()) {
+        (&Method::POST, "/v2/aliases") => async_change("64"),
+        (&Method::GET, "/v2/changes/64") => {
+            reply(200, include_str!("fixtures/change_unalias.json"))
+        }
+        // The automatic alias "hi" is still listed, disabled, while the manual "greet" is gone.
+        (&Method::GET, "/v2/aliases") => {
+            reply(200, include_str!("fixtures/get_aliases_disabled.json"))
+        }
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let result = snapd.client().unalias_and_wait("hi").await.unwrap();
+
+    let names = |changes: &[AliasChange]| {
+        changes
+            .iter()
+            .map(|change| change.alias.as_str().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert!(result.added.is_empty());
+    assert_eq!(names(&result.disabled), ["hi"]);
+    assert_eq!(names(&result.removed), ["greet"]);
+}
@@ -0,0 +1,31 @@
+//! Snaps' apps and services.
+
+mod common;
+
+use common::{error, sync, FakeSnapd};
+use serde_json::json;
+
+fn service(snap: &str, name: &str, active: bool) -> serde_json::Value {
+    json!({
+        "snap": snap, "name": name, "daemon": "simple", "daemon-scope": "system",
+        "enabled": true, "active": active,
+    })
+}
+
+#[tokio::test]
+async fn is_running_with_an_active_service() {
+    let snapd = FakeSnapd::new(|request| match request.uri.as_str() {
+        "/v2/apps?names=lxd&select=service" => sync(json!([
+            service("lxd", "activate", false),
+            service("lxd", "daemon", true),
+        ])),
+        "/v2/apps?names=nginx&select=service" => sync(json!([service("nginx", "nginx", false)])),
+        _ => error(404, "app-not-found", "snap has no services"),
+    });
+    let client = snapd.client();
+
+    assert!(client.is_running("lxd".into()).await.unwrap());
+    assert!(!client.is_running("nginx".into()).await.unwrap());
+    // A snap without services can't be told to be running.
+    assert!(!client.is_running("hello".into()).await.unwrap());
+}
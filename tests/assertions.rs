@@ -0,0 +1,61 @@
+//! Fetching assertions.
+
+mod common;
+
+use common::{error, reply, FakeSnapd};
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use snapd::api::assertions::SnapNameFromId;
+
+const DECLARATIONS: &str = include_str!("fixtures/snap_declarations.txt");
+
+#[tokio::test]
+async fn assertions_raw_returns_the_bytes_unchanged() {
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/assertions/snap-declaration" => {
+            let mut reply = reply(200, DECLARATIONS);
+            reply.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/x.ubuntu.assertion"),
+            );
+            reply
+        }
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let raw = snapd
+        .client()
+        .assertions_raw(
+            "snap-declaration",
+            &[("series", "16"), ("snap-name", "firefox")],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(raw, DECLARATIONS.as_bytes());
+    assert_eq!(
+        snapd.received()[0].uri,
+        "/v2/assertions/snap-declaration?series=16&snap-name=firefox"
+    );
+}
+
+#[tokio::test]
+async fn snap_name_from_id_reads_the_text_form() {
+    // Older versions of `snapd` ignore being asked for JSON, and send the assertions as text.
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/assertions/snap-declaration" => {
+            let mut reply = reply(200, DECLARATIONS);
+            reply.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/x.ubuntu.assertion"),
+            );
+            reply
+        }
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let name = SnapNameFromId::get_name("buPKUD3TKqCOgLEjjHx5kSiCpIs5cMuQ".into(), &snapd.client())
+        .await
+        .unwrap();
+
+    assert_eq!(name.as_str(), "hello-world");
+}
@@ -0,0 +1,56 @@
+//! The client making blocking calls.
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{sync, FakeSnapd};
+use serde_json::json;
+use snapd::{
+    api::system_info::GetSystemInfo,
+    blocking::{BlockingError, BlockingSnapdClient},
+};
+
+fn system_info() -> serde_json::Value {
+    json!({"series": "16", "version": "2.61.3", "os-release": {"id": "ubuntu"}})
+}
+
+#[test]
+fn blocking_client_outside_a_runtime() {
+    let snapd = FakeSnapd::new(|_| sync(system_info()));
+    let client = BlockingSnapdClient::from_client(snapd.client()).unwrap();
+
+    assert_eq!(client.get(&GetSystemInfo).unwrap().version, "2.61.3");
+    assert_eq!(client.get(&GetSystemInfo).unwrap().series, "16");
+    assert_eq!(snapd.received().len(), 2);
+}
+
+#[tokio::test]
+async fn blocking_client_inside_a_runtime() {
+    let snapd = FakeSnapd::new(|_| sync(system_info()));
+    let client = BlockingSnapdClient::from_client(snapd.client()).unwrap();
+
+    assert!(matches!(
+        client.get(&GetSystemInfo),
+        Err(BlockingError::InRuntime)
+    ));
+    assert!(snapd.received().is_empty());
+    // Dropping the client's own runtime here mustn't panic.
+    drop(client);
+}
+
+#[tokio::test]
+async fn blocking_client_used_then_dropped_inside_a_runtime() {
+    let snapd = FakeSnapd::new(|_| sync(system_info()));
+    let client = BlockingSnapdClient::from_client(snapd.client()).unwrap();
+    let client = std::thread::spawn(move || {
+        client.get(&GetSystemInfo).unwrap();
+        client
+    })
+    .join()
+    .unwrap();
+
+    // The client's pooled connection is driven by its own runtime, which is shut down here.
+    drop(client);
+    assert_eq!(snapd.received().len(), 1);
+}
@@ -0,0 +1,347 @@
+//! Waiting on changes.
+
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use common::{async_change, change, error, sync, FakeSnapd};
+use hyper::Method;
+use serde_json::json;
+use snapd::{
+    api::changes::{ChangeId, ChangeProgress, ChangeStatus},
+    CancellationToken, SnapdClient, SnapdPool, SnapdRequestError,
+};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn cancelling_stops_waiting_mid_poll() {
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/changes/5" => sync(change("5", "Doing")),
+        _ => error(404, "not-found", "not found"),
+    });
+    let token = CancellationToken::new();
+    let client = snapd.client().with_cancellation(token.clone());
+
+    let waiting = tokio::spawn(async move { client.wait_for_change(&ChangeId::from("5")).await });
+    // Let it poll a couple of times, so it's cancelled between polls.
+    while snapd.received().len() < 2 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    token.cancel();
+
+    let result = tokio::time::timeout(Duration::from_secs(1), waiting)
+        .await
+        .expect("cancelling stops the wait promptly")
+        .unwrap();
+    assert!(matches!(result, Err(SnapdRequestError::Cancelled)));
+}
+
+fn system_info(version: &str) -> serde_json::Value {
+    json!({"series": "16", "version": version, "os-release": {"id": "ubuntu"}})
+}
+
+#[tokio::test]
+async fn waiting_via_notices_long_polls_for_a_change_update() {
+    let updated = AtomicBool::new(false);
+    let snapd = FakeSnapd::new(move |request| match request.path() {
+        "/v2/system-info" => sync(system_info("2.61.3")),
+        "/v2/changes/9" if updated.load(Ordering::SeqCst) => sync(change("9", "Done")),
+        "/v2/changes/9" => sync(change("9", "Doing")),
+        "/v2/notices" => {
+            updated.store(true, Ordering::SeqCst);
+            sync(json!([{
+                "id": "31", "type": "change-update", "key": "9",
+                "first-occurred": "2024-05-01T10:00:00Z",
+                "last-occurred": "2024-05-01T10:00:05Z",
+                "last-repeated": "2024-05-01T10:00:05Z",
+                "occurrences": 3,
+                "last-data": {"kind": "install-snap"},
+            }]))
+        }
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let change = snapd
+        .client()
+        .wait_for_change_via_notices(&ChangeId::from("9"))
+        .await
+        .unwrap();
+
+    assert_eq!(change.status, ChangeStatus::Done);
+    let uris: Vec<_> = snapd.received().into_iter().map(|r| r.uri).collect();
+    assert_eq!(
+        uris,
+        [
+            "/v2/system-info",
+            "/v2/changes/9",
+            "/v2/notices?types=change-update&keys=9&timeout=30s",
+            "/v2/changes/9",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn waiting_via_notices_needs_snapd_2_59() {
+    for (version, uses_notices) in [("2.59", true), ("2.58.99+git12.abc", false)] {
+        let polls = AtomicUsize::new(0);
+        let snapd = FakeSnapd::new(move |request| match request.path() {
+            "/v2/system-info" => sync(system_info(version)),
+            "/v2/changes/9" if polls.fetch_add(1, Ordering::SeqCst) == 0 => {
+                sync(change("9", "Doing"))
+            }
+            "/v2/changes/9" => sync(change("9", "Done")),
+            "/v2/notices" => sync(json!([])),
+            _ => error(404, "not-found", "not found"),
+        });
+
+        snapd
+            .client()
+            .wait_for_change_via_notices(&ChangeId::from("9"))
+            .await
+            .unwrap();
+
+        let asked_for_notices = snapd.received().iter().any(|r| r.path() == "/v2/notices");
+        assert_eq!(asked_for_notices, uses_notices, "snapd {version}");
+    }
+}
+
+#[tokio::test]
+async fn waiting_via_notices_polls_on_an_older_snapd() {
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/system-info" => sync(system_info("2.58.3")),
+        "/v2/changes/9" => sync(change("9", "Done")),
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let change = snapd
+        .client()
+        .wait_for_change_via_notices(&ChangeId::from("9"))
+        .await
+        .unwrap();
+
+    assert_eq!(change.status, ChangeStatus::Done);
+    assert!(snapd.received().iter().all(|r| r.path() != "/v2/notices"));
+}
+
+/// The change `id` installing a snap, `done` quarters of the way through its download, with
+/// everything finished once `done` is 4.
+fn install_change(id: &str, status: &str, done: u64) -> serde_json::Value {
+    let task = |summary: &str, status: &str, done: u64| {
+        json!({
+            "id": summary, "kind": "task", "summary": summary, "status": status,
+            "progress": {"label": "", "done": done, "total": 4},
+            "spawn-time": "2024-05-01T10:00:00Z",
+        })
+    };
+    let mut change = change(id, status);
+    change["tasks"] = match done {
+        4 => json!([task("Download", "Done", 4), task("Mount", "Done", 4)]),
+        done => json!([task("Download", "Doing", done), task("Mount", "Do", 0)]),
+    };
+    change
+}
+
+#[tokio::test]
+async fn progress_is_sent_for_every_poll_in_order() {
+    let polls = AtomicUsize::new(0);
+    let snapd = FakeSnapd::new(move |request| match (&request.method, request.path()) {
+        (&Method::POST, "/v2/snaps/hello") => async_change("3"),
+        (&Method::GET, "/v2/changes/3") => match polls.fetch_add(1, Ordering::SeqCst) {
+            0 => sync(install_change("3", "Doing", 1)),
+            1 => sync(install_change("3", "Doing", 3)),
+            _ => sync(install_change("3", "Done", 4)),
+        },
+        _ => error(404, "not-found", "not found"),
+    });
+    let client = snapd.client();
+    // With room for only one update, and nothing read until the change is nearly done, any
+    // update that didn't wait for room would be lost.
+    let (sender, mut receiver) = mpsc::channel(1);
+
+    let waiting = tokio::spawn(async move {
+        client
+            .install_and_wait_with_progress("hello".into(), Default::default(), sender)
+            .await
+    });
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    let mut updates = Vec::new();
+    while let Some(update) = receiver.recv().await {
+        updates.push(update);
+    }
+
+    assert_eq!(waiting.await.unwrap().unwrap().status, ChangeStatus::Done);
+    let progress = |summary: &str, percent| ChangeProgress {
+        summary: summary.to_owned(),
+        percent,
+    };
+    assert_eq!(
+        updates,
+        [
+            progress("Download", 12.5),
+            progress("Download", 37.5),
+            progress("Install \"hello\" snap", 100.0),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn changes_for_a_snap_are_most_recent_first() {
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/changes" => {
+            let at = |id: &str, spawn_time: &str| {
+                let mut change = change(id, "Done");
+                change["spawn-time"] = json!(spawn_time);
+                change
+            };
+            sync(json!([
+                at("1", "2024-05-01T10:00:00Z"),
+                at("3", "2024-05-03T10:00:00Z"),
+                at("2", "2024-05-02T10:00:00+02:00"),
+            ]))
+        }
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let changes = snapd.client().changes_for("steam".into()).await.unwrap();
+
+    let ids: Vec<_> = changes.iter().map(|change| change.id.as_str()).collect();
+    assert_eq!(ids, ["3", "2", "1"]);
+    assert_eq!(snapd.received()[0].uri, "/v2/changes?select=all&for=steam");
+}
+
+#[tokio::test]
+async fn a_change_ready_on_the_first_check_is_returned_without_sleeping() {
+    let snapd = FakeSnapd::new(|request| match (&request.method, request.path()) {
+        (&Method::POST, "/v2/snaps/hello") => async_change("4"),
+        (&Method::GET, "/v2/changes/4") => sync(change("4", "Done")),
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let started = std::time::Instant::now();
+    let change = snapd
+        .client()
+        .install_and_wait("hello".into(), Default::default())
+        .await
+        .unwrap();
+
+    assert_eq!(change.status, ChangeStatus::Done);
+    // Well under the interval between checks, so none was waited out.
+    assert!(started.elapsed() < Duration::from_millis(200));
+    let polls = snapd
+        .received()
+        .into_iter()
+        .filter(|r| r.path() == "/v2/changes/4");
+    assert_eq!(polls.count(), 1);
+}
+
+#[tokio::test]
+async fn changes_are_waited_on_concurrently() {
+    let others_checked = Arc::new(AtomicUsize::new(0));
+    let polls = AtomicUsize::new(0);
+    let snapd = FakeSnapd::new({
+        let others_checked = others_checked.clone();
+        move |request| match request.path() {
+            // The first change is only ready on its second check, and its first is held up until
+            // the other changes have been checked, or a second has passed.
+            "/v2/changes/1" if polls.fetch_add(1, Ordering::SeqCst) == 0 => {
+                let started = Instant::now();
+                while others_checked.load(Ordering::SeqCst) < 2
+                    && started.elapsed() < Duration::from_secs(1)
+                {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                sync(change("1", "Doing"))
+            }
+            "/v2/changes/1" => sync(change("1", "Done")),
+            "/v2/changes/2" => {
+                others_checked.fetch_add(1, Ordering::SeqCst);
+                sync(change("2", "Error"))
+            }
+            _ => {
+                others_checked.fetch_add(1, Ordering::SeqCst);
+                error(404, "not-found", "not found")
+            }
+        }
+    });
+    let pool = SnapdPool::builder(snapd.manager()).max_size(4).build();
+    let client = SnapdClient::from_pool(pool.unwrap());
+    let ids = ["1", "2", "3"].map(ChangeId::from);
+
+    let started = Instant::now();
+    let results = client.wait_for_changes(&ids, None).await;
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    let [one, two, three] = &results[..] else {
+        panic!("expected three results, found {results:?}");
+    };
+    assert_eq!(one.as_ref().unwrap().status, ChangeStatus::Done);
+    assert_eq!(two.as_ref().unwrap().status, ChangeStatus::Error);
+    assert!(
+        matches!(three, Err(SnapdRequestError::Snapd { .. })),
+        "{three:?}"
+    );
+}
+
+#[tokio::test]
+async fn changes_not_ready_in_time_are_timed_out() {
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/changes/1" => sync(change("1", "Doing")),
+        _ => sync(change("2", "Done")),
+    });
+    let ids = ["1", "2"].map(ChangeId::from);
+    let timeout = Duration::from_millis(300);
+
+    let results = snapd.client().wait_for_changes(&ids, Some(timeout)).await;
+
+    assert!(
+        matches!(results[0], Err(SnapdRequestError::TimedOut(t)) if t == timeout),
+        "{:?}",
+        results[0]
+    );
+    assert_eq!(results[1].as_ref().unwrap().status, ChangeStatus::Done);
+}
+
+#[tokio::test]
+async fn abort_all_changes_aborts_those_in_progress() {
+    let snapd = FakeSnapd::new(|request| match (&request.method, request.uri.as_str()) {
+        (&Method::GET, "/v2/changes?select=in-progress") => sync(json!([
+            change("1", "Doing"),
+            change("2", "Do"),
+            change("3", "Doing"),
+        ])),
+        (&Method::POST, "/v2/changes/1" | "/v2/changes/2") => {
+            let id = request.path().rsplit('/').next().unwrap();
+            sync(change(id, "Abort"))
+        }
+        // The third became ready before it could be aborted.
+        (&Method::POST, "/v2/changes/3") => {
+            error(400, "", "cannot abort change 3 with nothing pending")
+        }
+        (&Method::GET, "/v2/changes/3") => sync(change("3", "Done")),
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let results = snapd.client().abort_all_changes().await.unwrap();
+
+    assert_eq!(results.len(), 2);
+    for id in ["1", "2"] {
+        let aborted = results[&ChangeId::from(id)].as_ref().unwrap();
+        assert_eq!(aborted.status, ChangeStatus::Abort);
+    }
+    let aborts: Vec<_> = snapd
+        .received()
+        .into_iter()
+        .filter(|r| r.method == Method::POST)
+        .map(|r| r.json())
+        .collect();
+    assert_eq!(aborts.len(), 3);
+    assert!(aborts
+        .iter()
+        .all(|body| *body == json!({"action": "abort"})));
+}
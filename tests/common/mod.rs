@@ -0,0 +1,410 @@
+//! A fake `snapd` for the integration tests, serving canned responses so the client can be tested
+//! end to end without a real `snapd`.
+//!
+//! Each fake runs on a thread of its own, with its own runtime, so it works the same from async
+//! tests and from blocking ones.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    net::SocketAddr,
+    path::PathBuf,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    thread,
+};
+
+use http_body_util::BodyExt;
+use hyper::{
+    body::{Body, Bytes, Frame, Incoming, SizeHint},
+    header::{CONTENT_TYPE, WWW_AUTHENTICATE},
+    server::conn::http1,
+    service::service_fn,
+    HeaderMap, Method, Request, Response,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use snapd::{SnapdClient, SnapdConnectionManager, SnapdPool};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, UnixListener},
+    runtime::Handle,
+    sync::oneshot,
+    task::AbortHandle,
+};
+
+/// A request the fake `snapd` received.
+#[derive(Clone, Debug)]
+pub struct Received {
+    pub method: Method,
+    /// The path and query the request was sent to.
+    pub uri: String,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl Received {
+    /// The path the request was sent to, without its query.
+    pub fn path(&self) -> &str {
+        self.uri.split('?').next().unwrap_or_default()
+    }
+
+    /// The body, decoded as JSON.
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).expect("request body is JSON")
+    }
+}
+
+/// A response from the fake `snapd`.
+pub type Reply = Response<ReplyBody>;
+
+/// The body of a [`Reply`], sent in the chunks it's made of.
+pub struct ReplyBody {
+    chunks: VecDeque<Bytes>,
+    /// Whether the length is sent up front, rather than the body being chunked.
+    sized: bool,
+}
+
+impl Body for ReplyBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        Poll::Ready(self.chunks.pop_front().map(|chunk| Ok(Frame::data(chunk))))
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        if self.sized {
+            SizeHint::with_exact(self.chunks.iter().map(|chunk| chunk.len() as u64).sum())
+        } else {
+            SizeHint::default()
+        }
+    }
+}
+
+/// Responds with `status` and the JSON `body`.
+pub fn reply(status: u16, body: impl Into<Bytes>) -> Reply {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(ReplyBody {
+            chunks: VecDeque::from([body.into()]),
+            sized: true,
+        })
+        .unwrap()
+}
+
+/// Responds with `chunks` using chunked encoding, so the length isn't known up front.
+pub fn chunked(chunks: impl IntoIterator<Item = &'static [u8]>) -> Reply {
+    Response::builder()
+        .status(200)
+        .body(ReplyBody {
+            chunks: chunks.into_iter().map(Bytes::from_static).collect(),
+            sized: false,
+        })
+        .unwrap()
+}
+
+/// Responds as `snapd` does to a request carried out before it responded.
+pub fn sync(result: Value) -> Reply {
+    reply(
+        200,
+        json!({"type": "sync", "status-code": 200, "status": "OK", "result": result}).to_string(),
+    )
+}
+
+/// Responds as `snapd` does to a request that started the change `id`.
+pub fn async_change(id: &str) -> Reply {
+    reply(
+        202,
+        json!({
+            "type": "async", "status-code": 202, "status": "Accepted", "result": null,
+            "change": id,
+        })
+        .to_string(),
+    )
+}
+
+/// Responds with an error of the given `kind`.
+pub fn error(status: u16, kind: &str, message: &str) -> Reply {
+    reply(
+        status,
+        json!({
+            "type": "error", "status-code": status, "status": "Error",
+            "result": {"message": message, "kind": kind},
+        })
+        .to_string(),
+    )
+}
+
+/// Responds as `snapd` does to a request that needs the user to log in, challenging them with
+/// `www_authenticate`.
+pub fn unauthorized(www_authenticate: &str) -> Reply {
+    let mut reply = error(401, "login-required", "access denied");
+    reply
+        .headers_mut()
+        .insert(WWW_AUTHENTICATE, www_authenticate.parse().unwrap());
+    reply
+}
+
+/// A change as `snapd` describes it, in `status`.
+pub fn change(id: &str, status: &str) -> Value {
+    json!({
+        "id": id,
+        "kind": "install-snap",
+        "summary": "Install \"hello\" snap",
+        "status": status,
+        "ready": matches!(status, "Hold" | "Done" | "Undone" | "Error"),
+        "spawn-time": "2024-05-01T10:00:00Z",
+    })
+}
+
+/// A snap as the store describes it, with only the fields the crate models, so it decodes with
+/// the `strict-decode` feature too.
+pub fn store_snap(name: &str, revision: &str) -> Value {
+    json!({
+        "id": format!("{name}-id"), "name": name, "summary": "A snap", "description": "A snap",
+        "version": "1.0", "revision": revision, "channel": "latest/stable",
+        "confinement": "strict", "type": "app", "status": "available",
+    })
+}
+
+/// An installed snap as `snapd` describes it, with only the fields the crate models.
+pub fn installed_snap(name: &str, revision: &str) -> Value {
+    json!({
+        "id": format!("{name}-id"), "name": name, "summary": "A snap", "description": "A snap",
+        "version": "1.0", "revision": revision, "channel": "stable",
+        "tracking-channel": "latest/stable", "confinement": "strict", "type": "app",
+        "status": "active", "install-date": "2024-05-01T10:00:00Z", "installed-size": 4096,
+    })
+}
+
+type Handler = dyn Fn(&Received) -> Reply + Send + Sync;
+
+struct Shared {
+    handler: Box<Handler>,
+    received: Mutex<Vec<Received>>,
+    connections: Mutex<Vec<AbortHandle>>,
+}
+
+/// Where a [`FakeSnapd`] listens.
+enum Address {
+    Unix(PathBuf),
+    #[cfg(target_os = "linux")]
+    Abstract(String),
+    Tcp(SocketAddr),
+}
+
+/// A fake `snapd`, answering each request with whatever its handler returns.
+pub struct FakeSnapd {
+    address: Address,
+    shared: Arc<Shared>,
+    runtime: Handle,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl FakeSnapd {
+    /// Listens on a new socket in the temporary directory.
+    pub fn new(handler: impl Fn(&Received) -> Reply + Send + Sync + 'static) -> Self {
+        let path = temp_socket_path();
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        listener.set_nonblocking(true).unwrap();
+        Self::start(Address::Unix(path), handler, |shared| async move {
+            let listener = UnixListener::from_std(listener).unwrap();
+            while let Ok((stream, _)) = listener.accept().await {
+                serve(&shared, stream);
+            }
+        })
+    }
+
+    /// Listens on `name` in Linux's abstract socket namespace.
+    #[cfg(target_os = "linux")]
+    pub fn abstract_namespace(
+        name: &str,
+        handler: impl Fn(&Received) -> Reply + Send + Sync + 'static,
+    ) -> Self {
+        use std::os::linux::net::SocketAddrExt;
+
+        let address = std::os::unix::net::SocketAddr::from_abstract_name(name).unwrap();
+        let listener = std::os::unix::net::UnixListener::bind_addr(&address).unwrap();
+        listener.set_nonblocking(true).unwrap();
+        Self::start(
+            Address::Abstract(name.to_owned()),
+            handler,
+            |shared| async move {
+                let listener = UnixListener::from_std(listener).unwrap();
+                while let Ok((stream, _)) = listener.accept().await {
+                    serve(&shared, stream);
+                }
+            },
+        )
+    }
+
+    /// Listens on a local TCP port.
+    pub fn tcp(handler: impl Fn(&Received) -> Reply + Send + Sync + 'static) -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        listener.set_nonblocking(true).unwrap();
+        Self::start(Address::Tcp(addr), handler, |shared| async move {
+            let listener = TcpListener::from_std(listener).unwrap();
+            while let Ok((stream, _)) = listener.accept().await {
+                serve(&shared, stream);
+            }
+        })
+    }
+
+    fn start<F: std::future::Future<Output = ()> + Send + 'static>(
+        address: Address,
+        handler: impl Fn(&Received) -> Reply + Send + Sync + 'static,
+        accept: impl FnOnce(Arc<Shared>) -> F + Send + 'static,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            handler: Box::new(handler),
+            received: Mutex::default(),
+            connections: Mutex::default(),
+        });
+
+        let (runtime_tx, runtime_rx) = std::sync::mpsc::channel();
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let accepting = shared.clone();
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime_tx.send(runtime.handle().clone()).unwrap();
+            runtime.block_on(async move {
+                tokio::select! {
+                    _ = accept(accepting) => {}
+                    _ = stopped => {}
+                }
+            });
+        });
+
+        Self {
+            address,
+            shared,
+            runtime: runtime_rx.recv().unwrap(),
+            _shutdown: shutdown,
+        }
+    }
+
+    /// Speaks HTTP over `stream`, as if it were a connection that had been accepted.
+    pub fn serve<S: AsyncRead + AsyncWrite + Send + Unpin + 'static>(&self, stream: S) {
+        let _guard = self.runtime.enter();
+        serve(&self.shared, stream);
+    }
+
+    /// A manager connecting to this fake.
+    pub fn manager(&self) -> SnapdConnectionManager {
+        match &self.address {
+            Address::Unix(path) => SnapdConnectionManager::new(path),
+            #[cfg(target_os = "linux")]
+            Address::Abstract(name) => SnapdConnectionManager::abstract_namespace(name),
+            Address::Tcp(addr) => SnapdConnectionManager::tcp(*addr),
+        }
+    }
+
+    /// A client connecting to this fake.
+    pub fn client(&self) -> SnapdClient {
+        SnapdClient::from_pool(SnapdPool::builder(self.manager()).build().unwrap())
+    }
+
+    /// The socket this fake listens on, if it listens on one in the filesystem.
+    pub fn socket_path(&self) -> Option<&PathBuf> {
+        match &self.address {
+            Address::Unix(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The TCP address this fake listens on, if it listens over TCP.
+    pub fn tcp_addr(&self) -> Option<SocketAddr> {
+        match &self.address {
+            Address::Tcp(addr) => Some(*addr),
+            _ => None,
+        }
+    }
+
+    /// The requests received so far, in the order they arrived.
+    pub fn received(&self) -> Vec<Received> {
+        self.shared.received.lock().unwrap().clone()
+    }
+
+    /// How many connections have been accepted so far.
+    pub fn connections(&self) -> usize {
+        self.shared.connections.lock().unwrap().len()
+    }
+
+    /// Hangs up on every connection accepted so far, as a restarting `snapd` would.
+    pub fn kill_connections(&self) {
+        for connection in self.shared.connections.lock().unwrap().iter() {
+            connection.abort();
+        }
+    }
+}
+
+impl Drop for FakeSnapd {
+    fn drop(&mut self) {
+        if let Address::Unix(path) = &self.address {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Serves HTTP over `stream` on the current runtime.
+fn serve<S: AsyncRead + AsyncWrite + Send + Unpin + 'static>(shared: &Arc<Shared>, stream: S) {
+    let handling = shared.clone();
+    let service = service_fn(move |request: Request<Incoming>| {
+        let shared = handling.clone();
+        async move {
+            let (parts, body) = request.into_parts();
+            let received = Received {
+                method: parts.method,
+                uri: parts.uri.to_string(),
+                headers: parts.headers,
+                body: body.collect().await?.to_bytes(),
+            };
+            shared.received.lock().unwrap().push(received.clone());
+            // Handlers may block, such as to hold up a response, without stalling other
+            // connections.
+            let reply = tokio::task::spawn_blocking(move || (shared.handler)(&received))
+                .await
+                .expect("handler panicked");
+            Ok::<_, hyper::Error>(reply)
+        }
+    });
+
+    let connection = tokio::spawn(async move {
+        let _ = http1::Builder::new()
+            .serve_connection(TokioIo::new(stream), service)
+            .await;
+    });
+    shared
+        .connections
+        .lock()
+        .unwrap()
+        .push(connection.abort_handle());
+}
+
+/// A path for a socket that no other test uses.
+pub fn temp_socket_path() -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "snapd-rs-test-{}-{}.sock",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    // Left behind by an earlier run with the same process ID.
+    let _ = std::fs::remove_file(&path);
+    path
+}
@@ -0,0 +1,179 @@
+//! Getting and setting snap configuration.
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use common::{async_change, change, error, sync, FakeSnapd};
+use hyper::Method;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use snapd::{
+    api::{
+        changes::{ChangeId, ChangeStatus},
+        snap::SnapName,
+    },
+    SnapdRequestError,
+};
+
+#[tokio::test]
+async fn set_snap_conf_and_wait_polls_until_ready() {
+    let polls = AtomicUsize::new(0);
+    let snapd = FakeSnapd::new(move |request| match (&request.method, request.path()) {
+        (&Method::PUT, "/v2/snaps/hello/conf") => async_change("7"),
+        (&Method::GET, "/v2/changes/7") => match polls.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => sync(change("7", "Doing")),
+            _ => sync(change("7", "Done")),
+        },
+        _ => error(404, "not-found", "not found"),
+    });
+    let conf = json!({"greeting": "hi"}).as_object().unwrap().clone();
+
+    let change = snapd
+        .client()
+        .set_snap_conf_and_wait("hello".into(), &conf)
+        .await
+        .unwrap();
+
+    assert_eq!(change.status, ChangeStatus::Done);
+    let received = snapd.received();
+    assert_eq!(received[0].json(), json!({"greeting": "hi"}));
+    let polls = received.iter().filter(|r| r.path() == "/v2/changes/7");
+    assert_eq!(polls.count(), 3);
+}
+
+#[tokio::test]
+async fn typed_conf_decodes_dotted_keys_into_a_struct() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Proxy {
+        http: String,
+        https: String,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Conf {
+        proxy: Proxy,
+    }
+
+    let snapd = FakeSnapd::new(|_| {
+        sync(json!({
+            "proxy.http": "http://proxy:3128",
+            "proxy.https": "http://proxy:3129",
+        }))
+    });
+
+    let conf: Conf = snapd
+        .client()
+        .get_conf_typed("core".into(), &["proxy.http", "proxy.https"])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        conf,
+        Conf {
+            proxy: Proxy {
+                http: "http://proxy:3128".into(),
+                https: "http://proxy:3129".into(),
+            },
+        }
+    );
+    assert_eq!(
+        snapd.received()[0].uri,
+        "/v2/snaps/core/conf?keys=proxy.http%2Cproxy.https"
+    );
+}
+
+fn confs(names: &[&'static str]) -> HashMap<SnapName<'static>, Map<String, Value>> {
+    names
+        .iter()
+        .map(|&name| {
+            let conf = json!({"greeting": "hi"}).as_object().unwrap().clone();
+            (SnapName::new(name), conf)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn set_conf_many_reports_each_snap() {
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/snaps/hello/conf" => async_change("7"),
+        _ => error(404, "snap-not-found", "snap \"steam\" is not installed"),
+    });
+
+    let mut results = snapd
+        .client()
+        .set_conf_many(confs(&["hello", "steam"]))
+        .await;
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results.remove(&SnapName::new("hello")).unwrap().unwrap(),
+        ChangeId::from("7")
+    );
+    let steam = results.remove(&SnapName::new("steam")).unwrap();
+    assert!(
+        matches!(steam, Err(SnapdRequestError::Snapd { status, .. }) if status == 404),
+        "{steam:?}"
+    );
+}
+
+#[tokio::test]
+async fn set_conf_many_reports_tasks_cancelled_by_shutdown() {
+    let snapd = FakeSnapd::new(|request| {
+        if request.path() == "/v2/snaps/steam/conf" {
+            // Holds up the response until well after the runtime has shut down.
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        async_change("7")
+    });
+    let client = snapd.client();
+    // The requests' tasks are spawned on a runtime of their own, which is shut down under them.
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+    let handle = runtime.handle().clone();
+
+    let setting = client.set_conf_many(confs(&["steam"]));
+    let mut setting = std::pin::pin!(setting);
+    let setting = std::future::poll_fn(|cx| {
+        let _runtime = handle.enter();
+        setting.as_mut().poll(cx)
+    });
+    let shutting_down = async {
+        while snapd.received().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        runtime.shutdown_background();
+    };
+    let (results, ()) = tokio::join!(setting, shutting_down);
+
+    let steam = &results[&SnapName::new("steam")];
+    assert!(
+        matches!(steam, Err(SnapdRequestError::Cancelled)),
+        "{steam:?}"
+    );
+}
+
+#[tokio::test]
+async fn unset_snap_conf_puts_null_values() {
+    let snapd = FakeSnapd::new(|_| async_change("9"));
+
+    let id = snapd
+        .client()
+        .unset_snap_conf("hello".into(), &["theme", "proxy.http"])
+        .await
+        .unwrap();
+
+    assert_eq!(id, ChangeId::from("9"));
+    let request = &snapd.received()[0];
+    assert_eq!(request.method, Method::PUT);
+    assert_eq!(request.uri, "/v2/snaps/hello/conf");
+    assert_eq!(request.json(), json!({"theme": null, "proxy.http": null}));
+}
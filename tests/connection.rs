@@ -0,0 +1,252 @@
+//! Connecting to `snapd` over the different transports.
+
+mod common;
+
+use std::{future, io, sync::Arc, time::Duration};
+
+use common::{sync, temp_socket_path, FakeSnapd};
+use deadpool::managed::PoolError;
+use serde_json::json;
+use snapd::{
+    api::system_info::GetSystemInfo, SnapdClient, SnapdConnectionError, SnapdConnectionManager,
+    SnapdPool, SnapdRequestError, Transport,
+};
+use tokio::io::DuplexStream;
+use url::Url;
+
+fn system_info() -> serde_json::Value {
+    json!({"series": "16", "version": "2.61.3", "os-release": {"id": "ubuntu"}})
+}
+
+#[tokio::test]
+async fn client_uses_a_custom_manager() {
+    let snapd = FakeSnapd::new(|_| sync(system_info()));
+    let manager = SnapdConnectionManager::new(snapd.socket_path().unwrap());
+    let pool = SnapdPool::builder(manager).max_size(1).build().unwrap();
+    let client = SnapdClient::from_pool(pool);
+
+    let info = client.get(&GetSystemInfo).await.unwrap();
+    assert_eq!(info.version, "2.61.3");
+    assert_eq!(snapd.received()[0].uri, "/v2/system-info");
+    assert_eq!(client.pool_status().max_size, 1);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn connects_to_an_abstract_socket() {
+    let name = format!("snapd-rs-test-{}", std::process::id());
+    let snapd = FakeSnapd::abstract_namespace(&name, |_| sync(system_info()));
+    let client = SnapdClient::from_pool(
+        SnapdPool::builder(SnapdConnectionManager::abstract_namespace(&format!(
+            "@{name}"
+        )))
+        .build()
+        .unwrap(),
+    );
+
+    assert_eq!(client.get(&GetSystemInfo).await.unwrap().version, "2.61.3");
+    assert_eq!(snapd.received().len(), 1);
+}
+
+#[tokio::test]
+async fn connects_over_tcp() {
+    let snapd = FakeSnapd::tcp(|_| sync(system_info()));
+    let addr = snapd.tcp_addr().unwrap();
+    let client = SnapdClient::over_tcp(addr);
+
+    assert_eq!(client.get(&GetSystemInfo).await.unwrap().version, "2.61.3");
+    let received = snapd.received();
+    assert_eq!(received[0].uri, "/v2/system-info");
+    assert_eq!(received[0].headers["host"], addr.to_string().as_str());
+}
+
+/// Connects to a fake over an in-memory pipe rather than a socket.
+struct DuplexTransport(Arc<FakeSnapd>);
+
+impl Transport for DuplexTransport {
+    type Stream = DuplexStream;
+
+    async fn connect(&self) -> io::Result<DuplexStream> {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        self.0.serve(server);
+        Ok(client)
+    }
+}
+
+#[tokio::test]
+async fn get_works_over_any_transport() {
+    let snapd = Arc::new(FakeSnapd::new(|_| sync(system_info())));
+    let client = SnapdClient::with_transport(DuplexTransport(snapd.clone()));
+
+    assert_eq!(client.get(&GetSystemInfo).await.unwrap().version, "2.61.3");
+    assert_eq!(snapd.received()[0].uri, "/v2/system-info");
+    assert_eq!(snapd.connections(), 1);
+}
+
+/// Connects like [`DuplexTransport`], but with a base URL that can't have a path.
+struct PathlessTransport(Arc<FakeSnapd>);
+
+impl Transport for PathlessTransport {
+    type Stream = DuplexStream;
+
+    async fn connect(&self) -> io::Result<DuplexStream> {
+        DuplexTransport(self.0.clone()).connect().await
+    }
+
+    fn base_url(&self) -> Url {
+        Url::parse("mailto:snapd@localhost").unwrap()
+    }
+}
+
+#[tokio::test]
+async fn base_url_without_a_path_is_an_error() {
+    let snapd = Arc::new(FakeSnapd::new(|_| sync(json!([]))));
+    let client = SnapdClient::with_transport(PathlessTransport(snapd.clone()));
+
+    assert!(matches!(
+        client.assertions_raw("account", &[]).await,
+        Err(SnapdRequestError::InvalidBaseUrl(url)) if url.scheme() == "mailto"
+    ));
+    assert!(snapd.received().is_empty());
+}
+
+/// Never finishes connecting, like a `snapd` that's wedged.
+struct StalledTransport;
+
+impl Transport for StalledTransport {
+    type Stream = DuplexStream;
+
+    async fn connect(&self) -> io::Result<DuplexStream> {
+        future::pending().await
+    }
+}
+
+fn is_timeout(result: Result<impl std::fmt::Debug, SnapdRequestError>, after: Duration) -> bool {
+    matches!(
+        result,
+        Err(SnapdRequestError::Connection(PoolError::Backend(SnapdConnectionError::Timeout(t))))
+            if t == after
+    )
+}
+
+#[tokio::test]
+async fn connect_timeout_gives_up_on_a_stalled_transport() {
+    let timeout = Duration::from_millis(50);
+    let manager =
+        SnapdConnectionManager::with_transport(StalledTransport).with_connect_timeout(timeout);
+    let client = SnapdClient::from_pool(SnapdPool::builder(manager).build().unwrap());
+
+    assert!(is_timeout(client.get(&GetSystemInfo).await, timeout));
+}
+
+#[cfg(feature = "tls")]
+#[tokio::test]
+async fn connect_timeout_covers_a_tls_handshake_that_never_finishes() {
+    use snapd::rustls::{pki_types::ServerName, ClientConfig, RootCertStore};
+    use tokio::net::TcpListener;
+
+    // Accepts connections, then never says anything on them.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let accepted = tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            held.push(stream);
+        }
+    });
+
+    let config = ClientConfig::builder()
+        .with_root_certificates(RootCertStore::empty())
+        .with_no_client_auth();
+    let timeout = Duration::from_millis(100);
+    let manager = SnapdConnectionManager::tls(
+        addr,
+        ServerName::try_from("localhost").unwrap(),
+        Arc::new(config),
+    )
+    .with_connect_timeout(timeout);
+    let client = SnapdClient::from_pool(SnapdPool::builder(manager).build().unwrap());
+
+    assert!(is_timeout(client.get(&GetSystemInfo).await, timeout));
+    accepted.abort();
+}
+
+#[tokio::test]
+async fn missing_socket_means_snapd_isnt_installed() {
+    let client = SnapdClient::from_pool(
+        SnapdPool::builder(SnapdConnectionManager::new(temp_socket_path()))
+            .build()
+            .unwrap(),
+    );
+
+    assert!(matches!(
+        client.get(&GetSystemInfo).await,
+        Err(SnapdRequestError::Connection(PoolError::Backend(
+            SnapdConnectionError::SnapdNotInstalled(_)
+        )))
+    ));
+}
+
+#[tokio::test]
+async fn warm_up_opens_connections_ahead_of_requests() {
+    let snapd = FakeSnapd::new(|_| sync(system_info()));
+    let pool = SnapdPool::builder(snapd.manager()).max_size(4).build();
+    let client = SnapdClient::from_pool(pool.unwrap());
+
+    client.warm_up(3).await.unwrap();
+
+    let status = client.pool_status();
+    assert_eq!(status.size, 3);
+    assert_eq!(status.available, 3);
+    client.get(&GetSystemInfo).await.unwrap();
+    assert_eq!(client.pool_status().size, 3);
+
+    // No more connections are opened than the pool can hold.
+    client.warm_up(10).await.unwrap();
+    assert_eq!(client.pool_status().available, 4);
+}
+
+#[tokio::test]
+async fn warm_up_gives_up_after_the_connect_timeout() {
+    let timeout = Duration::from_millis(50);
+    let manager =
+        SnapdConnectionManager::with_transport(StalledTransport).with_connect_timeout(timeout);
+    let client = SnapdClient::from_pool(SnapdPool::builder(manager).build().unwrap());
+
+    assert!(is_timeout(client.warm_up(2).await, timeout));
+    assert_eq!(client.pool_status().size, 0);
+}
+
+#[test]
+fn try_new_makes_a_client_without_connecting() {
+    // The pool connects lazily, so a client is made even where `snapd` isn't installed.
+    let client = SnapdClient::try_new().unwrap();
+    assert_eq!(client.pool_status().size, 0);
+}
+
+#[tokio::test]
+async fn keep_alive_evicts_a_connection_killed_while_idle() {
+    let snapd = FakeSnapd::new(|_| sync(system_info()));
+    let client = snapd.client().with_keep_alive(Duration::from_millis(50));
+
+    client.get(&GetSystemInfo).await.unwrap();
+    // The idle connection is checked over itself, without another being opened.
+    while snapd.received().len() < 3 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(snapd.connections(), 1);
+    assert_eq!(client.pool_status().size, 1);
+
+    snapd.kill_connections();
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while client.pool_status().size > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the dead connection is evicted");
+    assert_eq!(snapd.connections(), 1);
+
+    client.get(&GetSystemInfo).await.unwrap();
+    assert_eq!(snapd.connections(), 2);
+}
@@ -0,0 +1,304 @@
+//! Finding snaps in the store.
+
+mod common;
+
+use std::num::NonZeroUsize;
+
+use common::{error, installed_snap, reply, store_snap, sync, FakeSnapd};
+use serde_json::json;
+use snapd::{
+    api::{
+        assertions::SnapNameFromId,
+        find::{FindOneError, FindSnaps, SnapIdFromName},
+        snap::Revision,
+    },
+    SnapdClient,
+};
+
+#[tokio::test]
+async fn find_one_expects_exactly_one_result() {
+    let snapd = FakeSnapd::new(|request| match request.uri.as_str() {
+        "/v2/find?name=hello" => sync(json!([store_snap("hello", "42")])),
+        "/v2/find?name=twins" => sync(json!([store_snap("twins", "1"), store_snap("twins", "2")])),
+        "/v2/find?name=gone" => error(404, "snap-not-found", "snap not found"),
+        _ => sync(json!([])),
+    });
+    let client = snapd.client();
+
+    let hello = client.find_one("hello".into()).await.unwrap();
+    assert_eq!(hello.name.as_str(), "hello");
+
+    assert!(matches!(
+        client.find_one("nothing".into()).await,
+        Err(FindOneError::NoSnapsFound(name)) if name.as_str() == "nothing"
+    ));
+    assert!(matches!(
+        client.find_one("gone".into()).await,
+        Err(FindOneError::NoSnapsFound(name)) if name.as_str() == "gone"
+    ));
+    assert!(matches!(
+        client.find_one("twins".into()).await,
+        Err(FindOneError::Ambiguous { count: 2, .. })
+    ));
+}
+
+#[tokio::test]
+async fn snap_id_from_name_reads_the_stores_id() {
+    let snapd = FakeSnapd::new(|request| match request.uri.as_str() {
+        "/v2/find?name=steam" => sync(json!([store_snap("steam", "200")])),
+        _ => error(404, "snap-not-found", "snap not found"),
+    });
+    let client = snapd.client();
+
+    let id = SnapIdFromName::get_id("steam".into(), &client)
+        .await
+        .unwrap();
+    assert_eq!(id.as_str(), "steam-id");
+    assert!(matches!(
+        SnapIdFromName::get_id("nothing".into(), &client).await,
+        Err(FindOneError::NoSnapsFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn lookup_cache_answers_repeated_lookups() {
+    let snapd = FakeSnapd::new(|request| match request.uri.as_str() {
+        "/v2/find?name=steam" => sync(json!([store_snap("steam", "200")])),
+        _ => error(404, "snap-not-found", "snap not found"),
+    });
+    let client = snapd
+        .client()
+        .with_lookup_cache(NonZeroUsize::new(8).unwrap());
+
+    let id = SnapIdFromName::get_id("steam".into(), &client)
+        .await
+        .unwrap();
+    let again = SnapIdFromName::get_id("steam".into(), &client)
+        .await
+        .unwrap();
+    assert_eq!(id, again);
+    // The name is remembered along with the ID, so the reverse lookup is cached too.
+    let name = SnapNameFromId::get_name(id, &client).await.unwrap();
+    assert_eq!(name.as_str(), "steam");
+
+    assert_eq!(snapd.received().len(), 1);
+}
+
+#[tokio::test]
+#[ignore = "needs snapd running, with access to the store"]
+async fn live_snap_id_from_name() {
+    let client = SnapdClient::new();
+
+    let id = SnapIdFromName::get_id("steam".into(), &client)
+        .await
+        .unwrap();
+    assert!(!id.as_str().is_empty());
+    let name = SnapNameFromId::get_name(id, &client).await.unwrap();
+    assert_eq!(name.as_str(), "steam");
+}
+
+#[tokio::test]
+async fn refresh_candidates_pair_each_refresh_with_the_installed_snap() {
+    let snapd = FakeSnapd::new(|request| match request.uri.as_str() {
+        "/v2/find?select=refresh" => {
+            let mut hello = store_snap("hello", "43");
+            hello["version"] = json!("1.3");
+            // Removed after the store was asked, so there's nothing to refresh from.
+            let gone = store_snap("gone", "7");
+            sync(json!([hello, gone]))
+        }
+        "/v2/snaps" => {
+            let mut hello = installed_snap("hello", "42");
+            hello["version"] = json!("1.2");
+            sync(json!([hello, installed_snap("other", "3")]))
+        }
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let candidates = snapd.client().refresh_candidates().await.unwrap();
+
+    assert_eq!(candidates.len(), 1);
+    let hello = &candidates[0];
+    assert_eq!(hello.name.as_str(), "hello");
+    assert_eq!(hello.installed_revision, Revision::new(42));
+    assert_eq!(hello.revision, Revision::new(43));
+    assert_ne!(hello.revision, hello.installed_revision);
+    assert_eq!(hello.installed_version, "1.2");
+    assert_eq!(hello.version, "1.3");
+}
+
+#[tokio::test]
+async fn exists_in_store_treats_no_results_as_false() {
+    let snapd = FakeSnapd::new(|request| match request.uri.as_str() {
+        "/v2/find?name=hello" => sync(json!([store_snap("hello", "42")])),
+        "/v2/find?name=empty" => sync(json!([])),
+        _ => error(404, "snap-not-found", "snap not found"),
+    });
+    let client = snapd.client();
+
+    assert!(client.exists_in_store("hello".into()).await.unwrap());
+    assert!(!client.exists_in_store("empty".into()).await.unwrap());
+    assert!(!client.exists_in_store("gone".into()).await.unwrap());
+}
+
+#[tokio::test]
+async fn language_is_sent_as_accept_language() {
+    let snapd = FakeSnapd::new(|_| sync(json!([store_snap("hello", "42")])));
+    let client = snapd.client();
+
+    let french = FindSnaps::builder().query("hello").language("fr").build();
+    client.get(&french).await.unwrap();
+    client
+        .get(&FindSnaps::builder().query("hello").build())
+        .await
+        .unwrap();
+
+    let received = snapd.received();
+    assert_eq!(received[0].headers["accept-language"], "fr");
+    assert!(!received[1].headers.contains_key("accept-language"));
+}
+
+#[tokio::test]
+async fn dependencies_include_bases_and_content_providers() {
+    let snap = |name: &str, snap_type: &str, base: Option<&str>| {
+        let mut snap = store_snap(name, "1");
+        snap["type"] = json!(snap_type);
+        if let Some(base) = base {
+            snap["base"] = json!(base);
+        }
+        sync(json!([snap]))
+    };
+    let snapd = FakeSnapd::new(move |request| match request.uri.as_str() {
+        "/v2/find?name=steam" => snap("steam", "app", Some("core22")),
+        "/v2/find?name=gtk-common-themes" => snap("gtk-common-themes", "app", Some("core18")),
+        "/v2/find?name=core22" => snap("core22", "base", None),
+        "/v2/find?name=core18" => snap("core18", "base", None),
+        uri if uri.starts_with("/v2/interfaces?") => sync(json!([{
+            "name": "content",
+            "plugs": [{
+                "snap": "steam",
+                "plug": "gtk-3-themes",
+                "attrs": {"default-provider": "gtk-common-themes:gtk-3-themes"},
+            }],
+        }])),
+        _ => error(404, "snap-not-found", "snap not found"),
+    });
+
+    let dependencies = snapd.client().dependencies("steam".into()).await.unwrap();
+
+    let names: Vec<_> = dependencies
+        .snaps
+        .iter()
+        .map(|name| name.as_str())
+        .collect();
+    assert_eq!(names, ["core22", "gtk-common-themes", "core18"]);
+    assert!(dependencies.unresolved.is_empty());
+}
+
+#[tokio::test]
+async fn dependencies_the_store_cant_find_dont_fail_the_rest() {
+    let snapd = FakeSnapd::new(move |request| match request.uri.as_str() {
+        "/v2/find?name=steam" => {
+            let mut steam = store_snap("steam", "1");
+            steam["base"] = json!("core22");
+            sync(json!([steam]))
+        }
+        "/v2/find?name=core22" => {
+            let mut core22 = store_snap("core22", "1");
+            core22["type"] = json!("base");
+            sync(json!([core22]))
+        }
+        // Installed from a file, so only the installed copy describes it.
+        "/v2/snaps/local-themes" => {
+            let mut themes = installed_snap("local-themes", "x1");
+            themes["base"] = json!("core18");
+            sync(themes)
+        }
+        "/v2/snaps/core18" => {
+            let mut core18 = installed_snap("core18", "2");
+            core18["type"] = json!("base");
+            sync(core18)
+        }
+        uri if uri.starts_with("/v2/interfaces?") => sync(json!([{
+            "name": "content",
+            "plugs": [
+                {
+                    "snap": "steam",
+                    "plug": "gtk-3-themes",
+                    "attrs": {"default-provider": "local-themes"},
+                },
+                {
+                    "snap": "steam",
+                    "plug": "icon-themes",
+                    "attrs": {"default-provider": "gone-themes"},
+                },
+            ],
+        }])),
+        _ => error(404, "snap-not-found", "snap not found"),
+    });
+    let client = snapd.client();
+
+    let dependencies = client.dependencies("steam".into()).await.unwrap();
+
+    let names: Vec<_> = dependencies
+        .snaps
+        .iter()
+        .map(|name| name.as_str())
+        .collect();
+    assert_eq!(names, ["core22", "local-themes", "gone-themes", "core18"]);
+    assert!(matches!(
+        dependencies.unresolved.as_slice(),
+        [(name, FindOneError::NoSnapsFound(_))] if name.as_str() == "gone-themes"
+    ));
+
+    // The snap being resolved itself can't be skipped.
+    assert!(matches!(
+        client.dependencies("nothing".into()).await,
+        Err(FindOneError::NoSnapsFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn refresh_candidates_are_counted() {
+    let snapd = FakeSnapd::new(|request| match request.uri.as_str() {
+        "/v2/find?select=refresh" => sync(json!([
+            store_snap("firefox", "4173"),
+            store_snap("steam", "200"),
+            store_snap("hello", "43"),
+        ])),
+        _ => error(404, "not-found", "not found"),
+    });
+
+    assert_eq!(snapd.client().refresh_candidates_count().await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn a_common_id_may_match_several_snaps() {
+    let snapd = FakeSnapd::new(|request| match request.uri.as_str() {
+        "/v2/find?common-id=org.gnome.Calculator" => {
+            reply(200, include_str!("fixtures/find_common_id.json"))
+        }
+        _ => error(404, "snap-not-found", "snap not found"),
+    });
+    let client = snapd.client();
+
+    let found = client
+        .find_by_common_id("org.gnome.Calculator")
+        .await
+        .unwrap();
+    let names: Vec<_> = found.iter().map(|snap| snap.name.as_str()).collect();
+    assert_eq!(names, ["gnome-calculator", "gnome-calculator-nightly"]);
+    assert!(found
+        .iter()
+        .all(|snap| snap.common_ids == ["org.gnome.Calculator"]));
+
+    assert!(matches!(
+        client.find_one_by_common_id("org.gnome.Calculator").await,
+        Err(FindOneError::AmbiguousCommonId { count: 2, .. })
+    ));
+    assert!(client
+        .find_by_common_id("org.example.Missing")
+        .await
+        .unwrap()
+        .is_empty());
+}
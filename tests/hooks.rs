@@ -0,0 +1,75 @@
+//! Observing requests and responses through the client's hooks.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::{sync, unauthorized, FakeSnapd};
+use serde_json::json;
+use snapd::api::{snaps::GetSnaps, system_info::GetSystemInfo};
+
+#[tokio::test]
+async fn hooks_run_once_per_request() {
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/snaps" => sync(json!([])),
+        _ => sync(json!({"series": "16", "version": "2.61.3", "os-release": {"id": "ubuntu"}})),
+    });
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let responses = Arc::new(Mutex::new(Vec::new()));
+    let client = snapd
+        .client()
+        .on_request({
+            let requests = requests.clone();
+            move |request| {
+                requests
+                    .lock()
+                    .unwrap()
+                    .push((request.method.clone(), request.uri.to_string()))
+            }
+        })
+        .on_response({
+            let responses = responses.clone();
+            move |response| {
+                responses.lock().unwrap().push((
+                    response.uri.to_string(),
+                    response.status,
+                    response.body.len(),
+                ))
+            }
+        });
+
+    client.get(&GetSystemInfo).await.unwrap();
+    client.get(&GetSnaps).await.unwrap();
+
+    assert_eq!(
+        *requests.lock().unwrap(),
+        [
+            (hyper::Method::GET, "/v2/system-info".to_owned()),
+            (hyper::Method::GET, "/v2/snaps".to_owned()),
+        ]
+    );
+    let responses = responses.lock().unwrap();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].0, "/v2/system-info");
+    assert_eq!(responses[0].1, hyper::StatusCode::OK);
+    let empty_list =
+        json!({"type": "sync", "status-code": 200, "status": "OK", "result": []}).to_string();
+    assert_eq!(responses[1].2, empty_list.len());
+}
+
+#[tokio::test]
+async fn response_hook_sees_the_headers_of_a_401() {
+    let challenge = r#"Macaroon root="AgEMbG9jYWxob3N0", discharge="https://login.ubuntu.com/""#;
+    let snapd = FakeSnapd::new(move |_| unauthorized(challenge));
+    let headers = Arc::new(Mutex::new(None));
+    let client = snapd.client().on_response({
+        let headers = headers.clone();
+        move |response| *headers.lock().unwrap() = Some(response.headers.clone())
+    });
+
+    assert!(client.get(&GetSnaps).await.is_err());
+
+    let headers = headers.lock().unwrap().take().unwrap();
+    assert_eq!(headers[hyper::header::WWW_AUTHENTICATE], challenge);
+    assert_eq!(headers[hyper::header::CONTENT_TYPE], "application/json");
+}
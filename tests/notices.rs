@@ -0,0 +1,94 @@
+//! Following notices a page at a time.
+
+mod common;
+
+use std::{
+    future,
+    pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use common::{error, sync, FakeSnapd};
+use futures_core::Stream;
+use serde_json::json;
+use snapd::api::{notices::GetNotices, time::Timestamp};
+use url::Url;
+
+fn notice(id: &str, last_repeated: &str) -> serde_json::Value {
+    json!({
+        "id": id, "type": "change-update", "key": id,
+        "first-occurred": "2024-05-01T10:00:00Z",
+        "last-occurred": last_repeated,
+        "last-repeated": last_repeated,
+        "occurrences": 1,
+    })
+}
+
+/// The `after` a request for notices was sent with, if any.
+fn after(uri: &str) -> Option<Timestamp> {
+    let url = Url::parse(&format!("http://localhost{uri}")).unwrap();
+    let (_, after) = url.query_pairs().find(|(key, _)| key == "after")?;
+    Some(after.parse().unwrap())
+}
+
+#[tokio::test]
+async fn pages_follow_after_until_an_empty_page() {
+    let pages = AtomicUsize::new(0);
+    let snapd = FakeSnapd::new(move |request| match request.path() {
+        "/v2/notices" => match pages.fetch_add(1, Ordering::SeqCst) {
+            0 => sync(json!([
+                notice("1", "2024-05-01T10:00:05Z"),
+                notice("2", "2024-05-01T10:00:07Z"),
+            ])),
+            1 => sync(json!([notice("3", "2024-05-01T10:00:09Z")])),
+            _ => sync(json!([])),
+        },
+        _ => error(404, "not-found", "not found"),
+    });
+    let mut pages = snapd.client().pages(GetNotices::default());
+
+    let mut ids = Vec::new();
+    while let Some(page) = future::poll_fn(|cx| Pin::new(&mut pages).poll_next(cx)).await {
+        let page = page.unwrap();
+        ids.push(
+            page.iter()
+                .map(|notice| notice.id.to_string())
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    assert_eq!(ids, [vec!["1", "2"], vec!["3"]]);
+    let afters: Vec<_> = snapd.received().iter().map(|r| after(&r.uri)).collect();
+    assert_eq!(
+        afters,
+        [
+            None,
+            Some("2024-05-01T10:00:07Z".parse().unwrap()),
+            Some("2024-05-01T10:00:09Z".parse().unwrap()),
+        ]
+    );
+    // Once ended, the pages stay ended without asking again.
+    assert!(pages.next_page().await.unwrap().is_none());
+    assert_eq!(snapd.received().len(), 3);
+}
+
+#[tokio::test]
+async fn a_failed_page_is_fetched_again() {
+    let pages = AtomicUsize::new(0);
+    let snapd = FakeSnapd::new(move |_| match pages.fetch_add(1, Ordering::SeqCst) {
+        0 => error(500, "", "internal error"),
+        1 => sync(json!([notice("1", "2024-05-01T10:00:05Z")])),
+        _ => sync(json!([])),
+    });
+    let mut pages = snapd.client().pages(GetNotices::default());
+
+    assert!(pages.next_page().await.is_err());
+    assert_eq!(pages.next_page().await.unwrap().unwrap().len(), 1);
+    assert!(pages.next_page().await.unwrap().is_none());
+
+    let afters: Vec<_> = snapd.received().iter().map(|r| after(&r.uri)).collect();
+    assert_eq!(
+        afters,
+        [None, None, Some("2024-05-01T10:00:05Z".parse().unwrap())]
+    );
+}
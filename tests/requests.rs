@@ -0,0 +1,218 @@
+//! How requests are framed and responses read.
+
+mod common;
+
+use common::{async_change, chunked, error, installed_snap, reply, sync, unauthorized, FakeSnapd};
+use hyper::{Method, StatusCode};
+use serde_json::json;
+use snapd::{
+    api::{
+        snaps::{GetSnap, GetSnaps},
+        snapshots::{ExportSnapshot, SnapshotSetId},
+    },
+    SnapdRequestError,
+};
+
+#[tokio::test]
+async fn content_length_matches_the_body() {
+    let snapd = FakeSnapd::new(|_| async_change("1"));
+    let conf = json!({"greeting": "hello there"})
+        .as_object()
+        .unwrap()
+        .clone();
+
+    snapd
+        .client()
+        .set_snap_conf("hello".into(), &conf)
+        .await
+        .unwrap();
+
+    let request = &snapd.received()[0];
+    assert_eq!(
+        request.headers["content-length"],
+        request.body.len().to_string().as_str()
+    );
+    assert!(!request.headers.contains_key("transfer-encoding"));
+    assert_eq!(request.json(), json!({"greeting": "hello there"}));
+}
+
+#[tokio::test]
+async fn dropping_a_stream_early_frees_its_connection() {
+    let snapd = FakeSnapd::new(|_| reply(200, vec![0; 4 << 20]));
+    let client = snapd.client();
+    let export = ExportSnapshot {
+        set: SnapshotSetId(1),
+    };
+
+    let mut stream = client.get_stream(&export).await.unwrap();
+    assert!(stream.chunk().await.unwrap().is_some());
+    drop(stream);
+    assert_eq!(client.pool_status().size, 0);
+
+    // Read to the end, the connection goes back to the pool to be reused.
+    let mut stream = client.get_stream(&export).await.unwrap();
+    let mut length = 0;
+    while let Some(chunk) = stream.chunk().await.unwrap() {
+        length += chunk.len();
+    }
+    assert_eq!(length, 4 << 20);
+    drop(stream);
+    assert_eq!(client.pool_status().available, 1);
+
+    client.get_stream(&export).await.unwrap();
+    assert_eq!(snapd.connections(), 2);
+}
+
+#[tokio::test]
+async fn unauthorized_carries_the_challenge() {
+    let snapd = FakeSnapd::new(|_| {
+        unauthorized(r#"Macaroon root="AgEMbG9j", discharge="https://login.ubuntu.com/""#)
+    });
+
+    let Err(SnapdRequestError::Unauthorized { challenge, error }) =
+        snapd.client().get(&GetSnaps).await
+    else {
+        panic!("expected a 401 to be Unauthorized");
+    };
+    let challenge = challenge.unwrap();
+    assert_eq!(challenge.scheme, "Macaroon");
+    assert_eq!(challenge.param("root"), Some("AgEMbG9j"));
+    assert_eq!(
+        challenge.param("discharge"),
+        Some("https://login.ubuntu.com/")
+    );
+    assert_eq!(error.kind.as_deref(), Some("login-required"));
+}
+
+#[tokio::test]
+async fn head_returns_only_the_status() {
+    let snapd = FakeSnapd::new(|request| match (&request.method, request.path()) {
+        (&Method::HEAD, "/v2/snaps/hello") => sync(installed_snap("hello", "42")),
+        (&Method::HEAD, "/v2/snaps/gone") => error(404, "snap-not-found", "snap not installed"),
+        _ => error(405, "", "method not allowed"),
+    });
+    let client = snapd.client();
+
+    let hello = GetSnap {
+        name: "hello".into(),
+    };
+    assert_eq!(client.head(&hello).await.unwrap(), StatusCode::OK);
+    let gone = GetSnap {
+        name: "gone".into(),
+    };
+    assert_eq!(client.head(&gone).await.unwrap(), StatusCode::NOT_FOUND);
+    assert!(matches!(
+        client.head(&GetSnaps).await,
+        Err(SnapdRequestError::MethodNotAllowed(Method::HEAD))
+    ));
+    assert!(snapd.received().iter().all(|r| r.method == Method::HEAD));
+}
+
+#[tokio::test]
+async fn bodies_over_the_size_limit_are_errors() {
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/snaps" => sync(json!([
+            installed_snap("hello", "42"),
+            installed_snap("jq", "6"),
+        ])),
+        "/v2/snaps/hello" => sync(installed_snap("hello", "42")),
+        "/v2/assertions/account" => chunked([&b"type: account\n"[..], &[b'x'; 1024][..]]),
+        _ => error(404, "not-found", "not found"),
+    });
+    let client = snapd.client().with_max_response_size(512);
+
+    // With its length given up front.
+    assert!(matches!(
+        client.get(&GetSnaps).await,
+        Err(SnapdRequestError::ResponseTooLarge { limit: 512 })
+    ));
+    // And without, only found out partway through.
+    assert!(matches!(
+        client.assertions_raw("account", &[]).await,
+        Err(SnapdRequestError::ResponseTooLarge { limit: 512 })
+    ));
+    // A body within the limit is still read, over a fresh connection.
+    let hello = GetSnap {
+        name: "hello".into(),
+    };
+    assert_eq!(client.get(&hello).await.unwrap().name.as_str(), "hello");
+}
+
+#[tokio::test]
+async fn snapctl_sends_the_context_and_reads_a_failed_command() {
+    let snapd = FakeSnapd::new(|_| {
+        reply(
+            200,
+            json!({
+                "type": "error", "status-code": 200, "status": "OK",
+                "result": {
+                    "message": "unknown option",
+                    "kind": "unsuccessful",
+                    "value": {"stderr": "error: unknown option\n", "exit-code": 2},
+                },
+            })
+            .to_string(),
+        )
+    });
+
+    let output = snapd
+        .client()
+        .snapctl("f3Vrb2tpZQ", &["get", "--bogus"])
+        .await
+        .unwrap();
+
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(output.stderr, "error: unknown option\n");
+    let request = &snapd.received()[0];
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.uri, "/v2/snapctl");
+    assert_eq!(
+        request.json(),
+        json!({"context-id": "f3Vrb2tpZQ", "args": ["get", "--bogus"]})
+    );
+}
+
+#[tokio::test]
+async fn set_model_sends_the_assertion_as_json() {
+    let snapd = FakeSnapd::new(|_| async_change("12"));
+    let assertion = "type: model\nauthority-id: acme\n\nc2lnbmF0dXJl\n";
+
+    let id = snapd.client().set_model(assertion).await.unwrap();
+
+    assert_eq!(id.as_str(), "12");
+    let request = &snapd.received()[0];
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.uri, "/v2/model");
+    assert_eq!(request.headers["content-type"], "application/json");
+    assert_eq!(request.json(), json!({"new-model": assertion}));
+}
+
+#[tokio::test]
+async fn snapshots_are_exported_chunk_by_chunk_and_imported() {
+    let snapd = FakeSnapd::new(|request| match (&request.method, request.path()) {
+        (&Method::GET, "/v2/snapshots/7/export") => {
+            chunked([&b"meta.json"[..], &b"archive.tgz"[..], &b"\0\0\0\0"[..]])
+        }
+        (&Method::POST, "/v2/snapshots") => sync(json!({"set-id": 8, "snaps": ["hello"]})),
+        _ => error(404, "not-found", "not found"),
+    });
+    let client = snapd.client();
+
+    let mut export = client.export_snapshot(SnapshotSetId(7)).await.unwrap();
+    assert_eq!(export.content_length(), None);
+    let mut chunks = Vec::new();
+    while let Some(chunk) = export.chunk().await.unwrap() {
+        chunks.push(chunk);
+    }
+    assert_eq!(chunks, [&b"meta.json"[..], b"archive.tgz", b"\0\0\0\0"]);
+
+    let archive = chunks.concat();
+    let imported = client.import_snapshot(&archive).await.unwrap();
+    assert_eq!(imported.set_id, SnapshotSetId(8));
+    let import = &snapd.received()[1];
+    assert_eq!(
+        import.headers["content-type"],
+        "application/x.snapd.snapshot"
+    );
+    assert_eq!(import.body, archive);
+}
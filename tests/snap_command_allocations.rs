@@ -0,0 +1,73 @@
+//! How many allocations decoding a `SnapCommand` takes, counted by a global allocator.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use serde::Deserialize;
+use snapd::api::snap::SnapCommand;
+
+/// Counts the allocations made on each thread, so tests running at once don't see each other's.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Runs `f`, returning what it returns along with how many allocations it made.
+fn counting<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let value = f();
+    (value, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn decoding_from_a_str_borrows_it() {
+    let json = r#""lxd.lxc""#;
+
+    let (command, allocations) = counting(|| serde_json::from_str::<SnapCommand>(json).unwrap());
+
+    assert_eq!(command, SnapCommand::new("lxd".into(), "lxc".into()));
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn parsing_an_owned_command_allocates_once() {
+    let raw = String::from("lxd.lxc");
+
+    let (command, allocations) = counting(|| SnapCommand::parse_owned(raw));
+
+    assert_eq!(command, SnapCommand::new("lxd".into(), "lxc".into()));
+    assert_eq!(allocations, 1);
+
+    let raw = String::from("hello");
+    let (command, allocations) = counting(|| SnapCommand::parse_owned(raw));
+    assert_eq!(command, SnapCommand::name_only("hello".into()));
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn decoding_an_owned_string_reuses_it() {
+    let value = serde_json::Value::String("lxd.lxc".to_owned());
+
+    let (command, allocations) = counting(|| SnapCommand::deserialize(value).unwrap());
+
+    assert_eq!(command, SnapCommand::new("lxd".into(), "lxc".into()));
+    // Only the app's name is copied out of the string.
+    assert_eq!(allocations, 1);
+}
@@ -0,0 +1,64 @@
+//! Installing, refreshing and removing snaps.
+
+mod common;
+
+use common::{async_change, error, installed_snap, sync, FakeSnapd};
+use hyper::Method;
+use serde_json::json;
+use snapd::api::{changes::ChangeId, snap::Revision, snaps::RefreshOutcome};
+
+#[tokio::test]
+async fn refresh_all_starts_a_change() {
+    let snapd = FakeSnapd::new(|request| match (&request.method, request.path()) {
+        (&Method::POST, "/v2/snaps") => async_change("12"),
+        _ => error(404, "not-found", "not found"),
+    });
+
+    let outcome = snapd.client().refresh_all().await.unwrap();
+
+    assert_eq!(outcome, RefreshOutcome::Started(ChangeId::from("12")));
+    assert_eq!(snapd.received()[0].json(), json!({"action": "refresh"}));
+}
+
+#[tokio::test]
+async fn refresh_all_with_nothing_to_refresh_is_up_to_date() {
+    let snapd = FakeSnapd::new(|_| error(400, "snap-no-update-available", "no snaps to refresh"));
+
+    let outcome = snapd.client().refresh_all().await.unwrap();
+
+    assert_eq!(outcome, RefreshOutcome::UpToDate);
+}
+
+#[tokio::test]
+async fn has_revision_looks_through_the_kept_revisions() {
+    let snapd = FakeSnapd::new(|request| match request.uri.as_str() {
+        "/v2/snaps?select=all&snaps=hello" => {
+            let mut previous = installed_snap("hello", "41");
+            previous["status"] = json!("installed");
+            sync(json!([installed_snap("hello", "42"), previous]))
+        }
+        _ => error(404, "not-found", "not found"),
+    });
+    let client = snapd.client();
+
+    assert!(client
+        .has_revision("hello".into(), Revision::new(41))
+        .await
+        .unwrap());
+    assert!(!client
+        .has_revision("hello".into(), Revision::new(40))
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn is_installed_treats_not_found_as_false() {
+    let snapd = FakeSnapd::new(|request| match request.path() {
+        "/v2/snaps/hello" => sync(installed_snap("hello", "42")),
+        _ => error(404, "snap-not-found", "snap not installed"),
+    });
+    let client = snapd.client();
+
+    assert!(client.is_installed("hello".into()).await.unwrap());
+    assert!(!client.is_installed("steam".into()).await.unwrap());
+}